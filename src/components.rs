@@ -1,25 +1,47 @@
 mod instance;
 pub use instance::*;
+mod debug;
+pub use debug::*;
 mod window;
 pub use window::*;
 mod device;
 pub use device::*;
+mod device_requirements;
+pub use device_requirements::*;
+mod queue_families;
+pub use queue_families::*;
 mod swapchain;
 pub use swapchain::*;
 mod command_pool;
 pub use command_pool::*;
 mod command_buffer;
 pub use command_buffer::*;
+mod upload_context;
+pub use upload_context::*;
+mod deletion_queue;
+pub use deletion_queue::*;
 mod fence;
 pub use fence::*;
+mod semaphore;
+pub use semaphore::*;
 mod surface;
 pub use surface::*;
 mod renderpass;
 pub use renderpass::*;
+mod renderpass_desc;
+pub use renderpass_desc::*;
+mod framebuffers;
+pub use framebuffers::*;
 mod buffer;
 pub use buffer::*;
 mod pipeline;
 pub use pipeline::*;
+mod shader_cache;
+pub use shader_cache::*;
+mod graphics_pipeline;
+pub use graphics_pipeline::*;
+mod compute_pipeline;
+pub use compute_pipeline::*;
 mod vertex;
 pub use vertex::*;
 mod buffers;
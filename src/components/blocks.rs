@@ -8,7 +8,7 @@ use ash::{vk, util::Align};
 use bytemuck::{Pod, Zeroable, bytes_of};
 use serde::Serialize;
 
-use crate::{Device, Instance, GO_Image, program_data, ProgramData, GO_Uniform, BufferType, GO_ImageArray, ImageArrayState};
+use crate::{Device, Instance, GO_Image, program_data, ProgramData, GO_Uniform, BufferType, GO_ImageArray, ImageArrayState, Deletion, Result};
 
 mod spawner;
 pub use spawner::*;
@@ -39,7 +39,7 @@ pub struct FrameDescriptorSet {
 #[derive(Clone, Copy, Debug)]
 pub struct BindingId(pub u32);
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct SetId(pub u32);
 
 pub struct DescriptorUniform {
@@ -121,50 +121,39 @@ impl BlockState {
 		frame_count: usize,
 		set_id: SetId,
 		descriptor_description: DescriptorDescription,
-	) -> Self { unsafe {
-
-		// OTS CMD BUFFER
-		
-		let cmd_alloc_info = vk::CommandBufferAllocateInfo::builder()
-			.level(vk::CommandBufferLevel::PRIMARY)
-			.command_pool(program_data.command_pool.command_pool)
-			.command_buffer_count(1)
-			.build();
-		let cmd_buffer = *program_data.device.device.allocate_command_buffers(&cmd_alloc_info).unwrap().first().unwrap_unchecked();
-		let cmd_begin_info = vk::CommandBufferBeginInfo::builder()
-			.flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT)
-			.build();
-		program_data.device.device.begin_command_buffer(cmd_buffer, &cmd_begin_info).unwrap();
+	) -> Result<Self> {
+
+		// BUFFERS (uploaded before returning)
+
+		let mut descriptor_data = program_data.immediate_transfer(
+			|cmd_buffer|
+			BlockState::create_buffers(
+				program_data,
+				cmd_buffer,
+				frame_count,
+				&descriptor_description,
+				set_id,
+			)
+		)?;
+		BlockState::acquire_images(
+			program_data,
+			&descriptor_data,
+		)?;
 
-		// BUFFERS & WRITES
+		// WRITES
 
-		let mut descriptor_data = BlockState::create_buffers(
-			program_data,
-			cmd_buffer,
-			frame_count,
-			&descriptor_description,
-			set_id,
-		);
 		let layouts = BlockState::create_writes(
 			program_data,
 			descriptor_set_layout,
 			frame_count,
 			&mut descriptor_data,
-		);
-
-		// SUBMIT OTS CMD BUFFER
-
-		program_data.device.device.end_command_buffer(cmd_buffer).unwrap();
-		let submit_info = vk::SubmitInfo::builder()
-			.command_buffers(&[cmd_buffer])
-			.build();
-		program_data.device.device.queue_submit(program_data.swapchain.present_queue, &[submit_info], vk::Fence::null()).unwrap();
-		Self {
+		)?;
+		Ok(Self {
 			layouts,
 			descriptor_data,
 			descriptor_description,
-		}
-	}}
+		})
+	}
 
 	pub fn update<T: Copy + Clone + Pod + Zeroable>(
 		&self,
@@ -197,123 +186,109 @@ impl BlockState {
 		}
 	}}
 
+	/// Binds the descriptor set of `frame` at this block's set.
+	pub fn bind(
+		&self,
+		device: &Device,
+		command_buffer: vk::CommandBuffer,
+		bind_point: vk::PipelineBindPoint,
+		pipeline_layout: vk::PipelineLayout,
+		frame: usize,
+	) { unsafe {
+		device.device.cmd_bind_descriptor_sets(
+			command_buffer,
+			bind_point,
+			pipeline_layout,
+			self.descriptor_data.set_id.0,
+			&[self.descriptor_data.descriptor_sets[frame]],
+			&[],
+		);
+	}}
+
+	/// Queues every buffer and image for destruction once the frames in flight have finished.
 	pub fn destroy_memory(
 		&mut self,
 		program_data: &ProgramData,
-	) { unsafe {
-		for frame_set in self.descriptor_data.frames.iter_mut() {
+	) -> Result<()> {
+		for frame_set in self.descriptor_data.frames.iter() {
 			for descriptor in frame_set.descriptors.iter() {
-				match descriptor {
-					FrameDescriptor::Uniform(uniform) => {
-						let buffer = match &uniform.buffer.buffer.buffer {
-							BufferType::Buffer(buffer) => buffer,
-							BufferType::Image(_) => unreachable!(),
-						};
-						program_data.get_allocator().destroy_buffer(
-							buffer.buffer,
-							&buffer.buffer_allocation,
-						).unwrap();
-					},
-					FrameDescriptor::Image(image) => {
-						match &image.image.image_buffer.buffer {
-							BufferType::Buffer(_) => unreachable!(),
-							BufferType::Image(image) => {
-								program_data.device.device.destroy_image_view(
-									image.image_view,
-									None,
-								);
-								program_data.get_allocator().destroy_image(
-									image.image,
-									&image.image_allocation,
-								).unwrap();
-								program_data.device.device.destroy_sampler(
-									image.image_sampler,
-									None,
-								);
-							},
-						}
-					},
-					FrameDescriptor::ImageArray(image_array) => {
-						match &image_array.image_array.image_buffer.buffer {
-							BufferType::Buffer(_) => unreachable!(),
-							BufferType::Image(image) => {
-								program_data.device.device.destroy_image_view(
-									image.image_view,
-									None,
-								);
-								program_data.get_allocator().destroy_image(
-									image.image,
-									&image.image_allocation,
-								).unwrap();
-								program_data.device.device.destroy_sampler(
-									image.image_sampler,
-									None,
-								);
-							},
-						}
-					},
+				let (buffer, staging_buffer) = match descriptor {
+					FrameDescriptor::Uniform(uniform) => (&uniform.buffer.buffer, None),
+					FrameDescriptor::Image(image) => (&image.image.image_buffer, image.image.image_staging_buffer.as_ref()),
+					FrameDescriptor::ImageArray(image_array) => (&image_array.image_array.image_buffer, image_array.image_array.image_staging_buffer.as_ref()),
+				};
+				buffer.defer_destroy(program_data);
+				if let Some(staging_buffer) = staging_buffer {
+					staging_buffer.defer_destroy(program_data);
 				}
 			}
 		}
-	}}
+		Ok(())
+	}
 
 	pub fn recreate_memory(
 		&mut self,
 		program_data: &ProgramData,
 		frame_count: usize,
-	) { unsafe {
-		
-		// OTS CMD BUFFER
-
-		let cmd_alloc_info = vk::CommandBufferAllocateInfo::builder()
-			.level(vk::CommandBufferLevel::PRIMARY)
-			.command_pool(program_data.command_pool.command_pool)
-			.command_buffer_count(1)
-			.build();
-		let cmd_buffer = *program_data.device.device.allocate_command_buffers(&cmd_alloc_info).unwrap().first().unwrap_unchecked();
-		let cmd_begin_info = vk::CommandBufferBeginInfo::builder()
-			.flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT)
-			.build();
-		program_data.device.device.begin_command_buffer(cmd_buffer, &cmd_begin_info).unwrap();
-
-		// RECREATE BUFFERS
-		
-		self.recreate_buffers(
+	) -> Result<()> { unsafe {
+
+		// RECREATE BUFFERS (uploaded before returning)
+
+		program_data.immediate_transfer(
+			|cmd_buffer|
+			self.recreate_buffers(
+				program_data,
+				cmd_buffer,
+				frame_count,
+			)
+		)?;
+		BlockState::acquire_images(
 			program_data,
-			cmd_buffer,
-			frame_count,
-		);
-		// if !self.descriptor_data.descriptor_sets.is_empty() {
-		// 	program_data.device.device.free_descriptor_sets(
-		// 		program_data.descriptor_pool.descriptor_pool,
-		// 		&self.descriptor_data.descriptor_sets,
-		// 	).unwrap();
-		// 	self.descriptor_data.descriptor_sets.clear();
-		// }
+			&self.descriptor_data,
+		)?;
+		program_data.deletion_queue.push(Deletion::DescriptorSets(
+			program_data.descriptor_pool.descriptor_pool,
+			std::mem::take(&mut self.descriptor_data.descriptor_sets),
+		));
 		let layouts = BlockState::create_writes(
 			program_data,
 			self.layouts.first().unwrap_unchecked(),
 			frame_count,
 			&mut self.descriptor_data,
-		);
+		)?;
 		self.layouts = layouts;
-
-		// SUBMIT OTS CMD BUFFER
-
-		program_data.device.device.end_command_buffer(cmd_buffer).unwrap();
-		let submit_info = vk::SubmitInfo::builder()
-			.command_buffers(&[cmd_buffer])
-			.build();
-		program_data.device.device.queue_submit(program_data.swapchain.present_queue, &[submit_info], vk::Fence::null()).unwrap();
+		Ok(())
 	}}
 	
+	/// Takes ownership of images uploaded on a dedicated transfer family for the graphics queue.
+	fn acquire_images(
+		program_data: &ProgramData,
+		descriptor_data: &BlockDescriptorData,
+	) -> Result<()> {
+		if !program_data.device.queues.has_dedicated_transfer() {
+			return Ok(());
+		}
+		program_data.immediate_submit(|cmd_buffer| {
+			for frame_set in descriptor_data.frames.iter() {
+				for descriptor in frame_set.descriptors.iter() {
+					match descriptor {
+						FrameDescriptor::Uniform(_) => {},
+						FrameDescriptor::Image(image) => image.image.acquire(&program_data.device, &cmd_buffer),
+						FrameDescriptor::ImageArray(image_array) => image_array.image_array.acquire(&program_data.device, &cmd_buffer),
+					}
+				}
+			}
+			Ok(())
+		})
+	}
+
 	fn create_buffers(
 		program_data: &ProgramData,
 		cmd_buffer: vk::CommandBuffer,
 		frame_count: usize,
 		descriptor_description: &DescriptorDescription,
 		set_id: SetId,
-	) -> BlockDescriptorData { unsafe {
+	) -> Result<BlockDescriptorData> { unsafe {
 		let mut frames: Vec<FrameDescriptorSet> = Vec::with_capacity(frame_count);
 		for _ in 0..frame_count {
 			let mut descriptors: Vec<FrameDescriptor> = Vec::with_capacity(8);
@@ -339,7 +314,7 @@ impl BlockState {
 							dd_type,
 						)
 					},
-				};
+				}?;
 				descriptors.push(frame);
 			}
 			frames.push(FrameDescriptorSet {
@@ -347,11 +322,11 @@ impl BlockState {
 				descriptors,
 			});
 		}
-		BlockDescriptorData {
+		Ok(BlockDescriptorData {
 			descriptor_sets: Vec::with_capacity(frames.len()),
 			set_id,
 			frames,
-		}
+		})
 	}}
 
 	fn recreate_buffers(
@@ -359,7 +334,7 @@ impl BlockState {
 		program_data: &ProgramData,
 		cmd_buffer: vk::CommandBuffer,
 		frame_count: usize,
-	) { unsafe {
+	) -> Result<()> { unsafe {
 		for fi in 0..frame_count {
 			self.descriptor_data.frames[fi].descriptor_writes.clear();
 			for (i, description) in self.descriptor_description.dd_types.iter().enumerate() {
@@ -368,7 +343,7 @@ impl BlockState {
 						let frame = create_uniform_buffer(
 							program_data,
 							dd_type,
-						);
+						)?;
 						self.descriptor_data.frames[fi].descriptors[i] = frame;
 					},
 					DDType::Image(dd_type) => {
@@ -376,7 +351,7 @@ impl BlockState {
 							program_data,
 							&cmd_buffer,
 							dd_type,
-						);
+						)?;
 						self.descriptor_data.frames[fi].descriptors[i] = frame;
 					},
 					DDType::ImageArray(dd_type) => {
@@ -384,12 +359,13 @@ impl BlockState {
 							program_data,
 							&cmd_buffer,
 							dd_type,
-						);
+						)?;
 						self.descriptor_data.frames[fi].descriptors[i] = frame;
 					},
 				};
 			}
 		}
+		Ok(())
 	}}
 
 	fn create_writes(
@@ -397,7 +373,7 @@ impl BlockState {
 		descriptor_set_layout: &vk::DescriptorSetLayout,
 		frame_count: usize,
 		descriptor_data: &mut BlockDescriptorData,
-	) -> Vec<vk::DescriptorSetLayout> { unsafe {
+	) -> Result<Vec<vk::DescriptorSetLayout>> { unsafe {
 		let layouts = vec![
 			*descriptor_set_layout;
 			frame_count
@@ -408,7 +384,7 @@ impl BlockState {
 			.build();
 		descriptor_data.descriptor_sets = program_data.device.device.allocate_descriptor_sets(
 			&info,
-		).unwrap();
+		)?;
 		for (i, frame) in descriptor_data.frames.iter_mut().enumerate() {
 			for descriptor in frame.descriptors.iter_mut() {
 				let write = match descriptor {
@@ -441,57 +417,57 @@ impl BlockState {
 				&[] as &[vk::CopyDescriptorSet],
 			);
 		}
-		layouts
+		Ok(layouts)
 	}}
 }
 
 fn create_uniform_buffer(
 	program_data: &ProgramData,
 	dd_type: &DDTypeUniform,
-) -> FrameDescriptor { unsafe {
+) -> Result<FrameDescriptor> { unsafe {
 	let mut dummy = Vec::with_capacity(dd_type.size);
 	dummy.resize(dd_type.size, 0u8);
 	let buffer = GO_Uniform::new(
 		program_data,
 		&dummy,
-	);
-	FrameDescriptor::Uniform(DescriptorUniform {
+	)?;
+	Ok(FrameDescriptor::Uniform(DescriptorUniform {
 		binding_id: dd_type.binding,
 		buffer,
 		size: dd_type.size,
-	})
+	}))
 }}
 
 fn create_image_buffer(
 	program_data: &ProgramData,
 	cmd_buffer: &vk::CommandBuffer,
 	dd_type: &DDTypeImage,
-) -> FrameDescriptor { unsafe {
+) -> Result<FrameDescriptor> { unsafe {
 	let image = GO_Image::new(
 		program_data,
 		&dd_type.file_abs,
-	);
+	)?;
 	image.transfer(&program_data.device, cmd_buffer);
-	FrameDescriptor::Image(DescriptorImage {
+	Ok(FrameDescriptor::Image(DescriptorImage {
 		binding_id: dd_type.binding,
 		image,
-	})
+	}))
 }}
 
 fn create_image_array_buffer(
 	program_data: &ProgramData,
 	cmd_buffer: &vk::CommandBuffer,
 	dd_type: &DDTypeImageArray,
-) -> FrameDescriptor { unsafe {
+) -> Result<FrameDescriptor> { unsafe {
 	let image_array = GO_ImageArray::new(
 		program_data,
 		&dd_type.ias,
-	);
+	)?;
 	image_array.transfer(&program_data.device, cmd_buffer);
-	FrameDescriptor::ImageArray(DescriptorImageArray {
+	Ok(FrameDescriptor::ImageArray(DescriptorImageArray {
 		binding_id: dd_type.binding,
 		image_array,
-	})
+	}))
 }}
 
 fn create_write_uniform(
@@ -2,18 +2,23 @@ use std::{sync::Arc, marker::PhantomData};
 
 use ash::vk;
 
-use crate::{Device, Instance, BlockState, BindingId, SetId, ProgramData};
+use crate::{Device, Instance, BlockState, BindingId, SetId, ProgramData, Result};
 
 pub trait BlockSpawnerGen {
+	/// The state is owned by the caller; wrap it in a lock when it must be shared and mutated.
 	fn spawn(
 		&self,
 		program_data: &ProgramData,
 		frame_count: usize,
-	) -> Arc<BlockState>;
+	) -> Result<BlockState>;
 	
 	fn layout(
 		&self,
 	) -> vk::DescriptorSetLayout;
+
+	fn set(
+		&self,
+	) -> SetId;
 }
 
 pub struct BlockSpawner<B: Block> {
@@ -28,14 +33,22 @@ impl<B: Block> BlockSpawner<B> {
 		device: &Arc<Device>,
 		binding: BindingId,
 		set: SetId,
-	) -> Self {
-		Self {
+	) -> Result<Self> {
+		Ok(Self {
 			_phantom: PhantomData,
-			layout: B::create_descriptor_set_layout(device, binding),
+			layout: B::create_descriptor_set_layout(device, binding)?,
 			binding,
 			set,
-		}
+		})
 	}
+
+	/// Spawned block states and every `BlockSpawnerExist` made from this spawner share the layout.
+	pub fn destroy(
+		&self,
+		device: &Device,
+	) { unsafe {
+		device.device.destroy_descriptor_set_layout(self.layout, None);
+	}}
 }
 
 impl<B: Block> BlockSpawnerGen for BlockSpawner<B> {
@@ -43,7 +56,7 @@ impl<B: Block> BlockSpawnerGen for BlockSpawner<B> {
 		&self,
 		program_data: &ProgramData,
 		frame_count: usize,
-	) -> Arc<BlockState> {
+	) -> Result<BlockState> {
 		B::create_block_state(
 			program_data,
 			&self.layout,
@@ -58,6 +71,12 @@ impl<B: Block> BlockSpawnerGen for BlockSpawner<B> {
 	) -> vk::DescriptorSetLayout {
 		self.layout
 	}
+
+	fn set(
+		&self,
+	) -> SetId {
+		self.set
+	}
 }
 
 pub struct BlockSpawnerExist<B: Block> {
@@ -85,7 +104,7 @@ impl<B: Block> BlockSpawnerGen for BlockSpawnerExist<B> {
 		&self,
 		program_data: &ProgramData,
 		frame_count: usize,
-	) -> Arc<BlockState> {
+	) -> Result<BlockState> {
 		B::create_block_state(
 			program_data,
 			&self.layout,
@@ -100,6 +119,12 @@ impl<B: Block> BlockSpawnerGen for BlockSpawnerExist<B> {
 	) -> vk::DescriptorSetLayout {
 		self.layout
 	}
+
+	fn set(
+		&self,
+	) -> SetId {
+		self.set
+	}
 }
 
 pub trait Block {
@@ -109,10 +134,10 @@ pub trait Block {
 		frame_count: usize,
 		binding: BindingId,
 		set: SetId,
-	) -> Arc<BlockState>;
+	) -> Result<BlockState>;
 
 	fn create_descriptor_set_layout(
 		device: &Arc<Device>,
 		binding: BindingId,
-	) -> vk::DescriptorSetLayout;
+	) -> Result<vk::DescriptorSetLayout>;
 }
\ No newline at end of file
@@ -4,7 +4,7 @@ use ash::{vk::{self, DeviceMemory, MemoryPropertyFlags}, util::Align};
 use bytemuck::{Pod, cast_slice};
 use vk_mem::{AllocationCreateInfo, MemoryUsage, AllocationCreateFlags, Allocation, AllocationInfo};
 
-use crate::{Device, Instance, ProgramData};
+use crate::{Device, Instance, ProgramData, Deletion, Result};
 
 #[derive(Clone, Debug)]
 pub enum RequirementType {
@@ -35,6 +35,24 @@ pub struct BufferTypeImage {
 	pub image_allocation: Allocation,
 }
 
+impl BufferType {
+	/// Everything backing this buffer or image, in destruction order.
+	pub fn deletions(
+		&self,
+	) -> Vec<Deletion> {
+		match self {
+			BufferType::Buffer(buffer) => vec![
+				Deletion::Buffer(buffer.buffer, buffer.buffer_allocation.clone()),
+			],
+			BufferType::Image(image) => vec![
+				Deletion::Sampler(image.image_sampler),
+				Deletion::ImageView(image.image_view),
+				Deletion::Image(image.image, image.image_allocation.clone()),
+			],
+		}
+	}
+}
+
 #[derive(Clone)]
 pub struct BufferGOMemory(pub vk::Buffer, pub vk::DeviceMemory);
 
@@ -50,41 +68,43 @@ impl BufferGO {
 	pub fn new<T>(
 		program_data: &ProgramData,
 		requirement_type: RequirementType,
-	) -> Self
+	) -> Result<Self>
 	where T: Default + Copy + Clone {
 		let buffer = allocate(
 			program_data,
 			&requirement_type,
-		);
+		)?;
 		match requirement_type {
 			RequirementType::Buffer(size, _) => {
-				Self {
+				Ok(Self {
 					count: 0,
 					capacity: size,
 					buffer,
 					requirement_type,
-				}
+				})
 			},
 			RequirementType::Image(extent, layers) => {
-				Self {
+				Ok(Self {
 					count: 0,
 					capacity: (extent.width * extent.height * 4) as usize,
 					buffer,
 					requirement_type,
-				}
+				})
 			}
 		}
 	}
 
+	/// Growing defers destruction of the old buffer until [`ProgramData::end_frame_deletions`]
+	/// next runs for the current frame, which render loops without a `FrameLoop` must call.
 	pub fn update<T>(
 		&mut self,
 		program_data: &ProgramData,
 		data: &[T],
-	) where
+	) -> Result<()> where
 	T: Default + Copy + Clone + Pod { unsafe {
 		if data.is_empty() {
 			self.count = 0;
-			return;
+			return Ok(());
 		}
 		let data: &[u8] = cast_slice(data);
 		if self.capacity < data.len() {
@@ -92,38 +112,18 @@ impl BufferGO {
 			while n_capacity < data.len() {
 				n_capacity *= 2;
 			}
-			match &self.buffer {
-				BufferType::Buffer(buffer) => {
-					program_data.get_allocator().destroy_buffer(
-						buffer.buffer,
-						&buffer.buffer_allocation,
-					).expect("failed to destroy buffer");
-					match &mut self.requirement_type {
-						RequirementType::Buffer(size, _) => {
-							*size = data.len();
-						},
-						RequirementType::Image(_, _) => unreachable!(),
-					}
-				},
-				BufferType::Image(image) => {
-					program_data.device.device.destroy_sampler(
-						image.image_sampler,
-						None,
-					);
-					program_data.device.device.destroy_image_view(
-						image.image_view,
-						None,
-					);
-					program_data.get_allocator().destroy_image(
-						image.image,
-						&image.image_allocation,
-					).expect("failed to destroy image");
+			// Frames in flight may still read the old buffer.
+			self.defer_destroy(program_data);
+			match &mut self.requirement_type {
+				RequirementType::Buffer(size, _) => {
+					*size = data.len();
 				},
+				RequirementType::Image(_, _) => {},
 			}
 			let buffer = allocate(
 				program_data,
 				&self.requirement_type,
-			);
+			)?;
 			self.buffer = buffer;
 			self.capacity = n_capacity;
 		}
@@ -134,13 +134,38 @@ impl BufferGO {
 			},
 			BufferType::Image(_) => { unimplemented!(); },
 		}
+		Ok(())
 	}}
+
+	/// Queues the buffer or image for destruction once the current frames in flight have finished.
+	pub fn defer_destroy(
+		&self,
+		program_data: &ProgramData,
+	) {
+		for deletion in self.buffer.deletions() {
+			program_data.deletion_queue.push(deletion);
+		}
+	}
+
+	/// The device must no longer use the buffer or image.
+	pub fn destroy(
+		&self,
+		program_data: &ProgramData,
+	) -> Result<()> {
+		for deletion in self.buffer.deletions() {
+			deletion.destroy(
+				&program_data.device,
+				program_data.get_allocator(),
+			)?;
+		}
+		Ok(())
+	}
 }
 
 fn allocate(
 	program_data: &ProgramData,
 	requriement_type: &RequirementType,
-) -> BufferType { unsafe {
+) -> Result<BufferType> { unsafe {
 	match requriement_type {
 		RequirementType::Buffer(size, usage_flags) => {
 			let allocation_info = AllocationCreateInfo {
@@ -164,16 +189,16 @@ fn allocate(
 			) = program_data.get_allocator().create_buffer(
 				&buffer_info,
 				&allocation_info,
-			).expect("failed to create buffer");
+			)?;
 			let mapped = buffer_allocation_info.get_mapped_data();
 			let buffer_offset = buffer_allocation_info.get_offset();
-			BufferType::Buffer(BufferTypeBuffer {
+			Ok(BufferType::Buffer(BufferTypeBuffer {
 				buffer,
 				buffer_offset,
 				buffer_allocation,
 				buffer_allocation_info,
 				mapped,
-			})
+			}))
 		},
 		RequirementType::Image(extent, layers) => {
 			let (
@@ -210,7 +235,7 @@ fn allocate(
 			) = program_data.get_allocator().create_image(
 				&image_info,
 				&allocation_info,
-			).expect("failed to create image");
+			)?;
 
 			// VIEW & SAMPLER
 
@@ -229,7 +254,7 @@ fn allocate(
 			let image_view = program_data.device.device.create_image_view(
 				&image_view_info,
 				None,
-			).expect("failed to create image view");
+			)?;
 			let image_sampler_info = vk::SamplerCreateInfo::builder()
 				.mag_filter(vk::Filter::NEAREST)
 				.min_filter(vk::Filter::NEAREST)
@@ -248,14 +273,14 @@ fn allocate(
 			let image_sampler = program_data.device.device.create_sampler(
 				&image_sampler_info,
 				None,
-			).expect("failed to create image sampler");
+			)?;
 
-			BufferType::Image(BufferTypeImage {
+			Ok(BufferType::Image(BufferTypeImage {
 				image,
 				image_view,
 				image_sampler,
 				image_allocation,
-			})
+			}))
 		},
 	}
 }}
\ No newline at end of file
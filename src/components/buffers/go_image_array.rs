@@ -5,7 +5,7 @@ use bytemuck::bytes_of;
 use nalgebra::{Vector2, vector};
 use serde::__private::de;
 
-use crate::{BufferGO, Instance, Device, RequirementType, ProgramData, ImageArrayState};
+use crate::{BufferGO, Instance, Device, RequirementType, ProgramData, QueueOwnership, upload_image_barrier, ImageArrayState, Result, VpbError};
 
 // #[derive(Clone)]
 #[allow(non_camel_case_types)]
@@ -21,52 +21,65 @@ impl GO_ImageArray {
 	pub fn new(
 		program_data: &ProgramData,
 		ias: &ImageArrayState,
-	) -> GO_ImageArray { unsafe {
+	) -> Result<GO_ImageArray> { unsafe {
 		let (
 			image_data,
 			image_size,
 			image_layers,
-		) = GO_ImageArray::load_disk(ias);
+		) = GO_ImageArray::load_disk(ias)?;
 		let mut image_staging_buffer = BufferGO::new::<u8>(
 			program_data,
 			RequirementType::Buffer(image_data.len(), vk::BufferUsageFlags::TRANSFER_SRC),
-		);
-		image_staging_buffer.update(program_data, &image_data);
+		)?;
+		image_staging_buffer.update(program_data, &image_data)?;
 		let image_buffer = BufferGO::new::<u8>(
 			program_data,
 			RequirementType::Image(vk::Extent2D::builder().width(image_size.x).height(image_size.y).build(), Some(image_layers)),
-		);
-		GO_ImageArray {
+		)?;
+		Ok(GO_ImageArray {
 			image_staging_buffer: Some(image_staging_buffer),
 			image_buffer,
 			image_size,
 			image_layers,
-		}
+		})
 	}}
 
 	pub fn update_image_array(
 		&mut self,
 		program_data: &ProgramData,
 		ias: &ImageArrayState,
-	) { unsafe {
+	) -> Result<()> { unsafe {
 		let (
 			image_data,
 			image_size,
 			image_layers,
-		) = GO_ImageArray::load_disk(ias);
+		) = GO_ImageArray::load_disk(ias)?;
 		self.image_size = image_size;
 		self.image_layers = image_layers;
 		if let Some(image_staging_buffer) = &mut self.image_staging_buffer {
-			image_staging_buffer.update(program_data, &image_data);
+			image_staging_buffer.update(program_data, &image_data)?;
 		} else {
 			let mut image_staging_buffer = BufferGO::new::<u8>(
 				program_data,
 				RequirementType::Buffer(image_data.len(), vk::BufferUsageFlags::TRANSFER_SRC),
-			);
-			image_staging_buffer.update(program_data, &image_data);
+			)?;
+			image_staging_buffer.update(program_data, &image_data)?;
 		}
+		Ok(())
 	}}
 
+	pub fn destroy(
+		&self,
+		program_data: &ProgramData,
+	) -> Result<()> {
+		if let Some(image_staging_buffer) = &self.image_staging_buffer {
+			image_staging_buffer.destroy(program_data)?;
+		}
+		self.image_buffer.destroy(program_data)
+	}
+
+	/// Records the upload on the transfer queue; with a dedicated transfer family the image
+	/// is released to graphics and must be acquired with [`Self::acquire`] before it is sampled.
 	pub fn transfer(
 		&self,
 		device: &Device,
@@ -77,6 +90,7 @@ impl GO_ImageArray {
 			command_buffer,
 			vk::ImageLayout::UNDEFINED,
 			vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+			QueueOwnership::Keep,
 		);
 		self.gpu_copy_image(device, command_buffer);
 		self.image_barrier(
@@ -84,12 +98,32 @@ impl GO_ImageArray {
 			command_buffer,
 			vk::ImageLayout::TRANSFER_DST_OPTIMAL,
 			vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+			device.queues.upload_release(),
+		);
+	}
+
+	/// Records the acquire on the graphics queue; nothing when uploads share the graphics family.
+	pub fn acquire(
+		&self,
+		device: &Device,
+		command_buffer: &vk::CommandBuffer,
+	) {
+		let ownership = device.queues.upload_acquire();
+		if ownership == QueueOwnership::Keep {
+			return;
+		}
+		self.image_barrier(
+			device,
+			command_buffer,
+			vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+			vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+			ownership,
 		);
 	}
 
 	fn load_disk(
 		ias: &ImageArrayState,
-	) -> (Vec<u8>, Vector2<u32>, u32) {
+	) -> Result<(Vec<u8>, Vector2<u32>, u32)> {
 		let mut files: Vec<(Vec<u8>, Vector2<u32>)> = Vec::with_capacity(1024);
 		for texture in ias.textures.iter() {
 			let mut path = String::with_capacity(ias.path.len() + 1 + texture.len() + 4);
@@ -97,7 +131,7 @@ impl GO_ImageArray {
 			path += "/";
 			path += &texture;
 			path += ".png";
-			files.push(GO_ImageArray::load_disk_single(&path));
+			files.push(GO_ImageArray::load_disk_single(&path)?);
 		}
 		// let paths = fs::read_dir(ias.path).expect("directory does not exist");
 		// for path in paths {
@@ -105,30 +139,39 @@ impl GO_ImageArray {
 		// 	files.push(GO_ImageArray::load_disk_single(&path));
 		// }
 		let mut buffer: Vec<u8> = Vec::with_capacity(1024 * files.len());
-		for file in files.iter() {
-			assert!(file.1.x == 16 && file.1.y == 16);
-			assert!(file.0.len() == 1024);
+		for (file, texture) in files.iter().zip(ias.textures.iter()) {
+			if file.1.x != 16 || file.1.y != 16 || file.0.len() != 1024 {
+				return Err(VpbError::ImageFormat(
+					format!("image array texture \"{}\" must be 16x16 RGBA", texture)
+				));
+			}
 			buffer.extend_from_slice(file.0.as_slice());
 		}
-		(buffer, vector![16, 16], files.len() as u32)
+		Ok((buffer, vector![16, 16], files.len() as u32))
 	}
 
 	fn load_disk_single(
 		file_name_abs: &str,
-	) -> (Vec<u8>, Vector2<u32>) {
-		let mut decoder = png::Decoder::new(File::open(file_name_abs).expect(
-			&format!("invalid image abs path \"{}\"", file_name_abs),
-		));
-		let header_info = decoder.read_header_info().unwrap();
+	) -> Result<(Vec<u8>, Vector2<u32>)> {
+		let mut decoder = png::Decoder::new(File::open(file_name_abs).map_err(
+			|error| VpbError::Io(file_name_abs.to_string(), error)
+		)?);
+		let header_info = decoder.read_header_info().map_err(
+			|error| VpbError::PngDecode(file_name_abs.to_string(), error)
+		)?;
 		if header_info.bytes_per_pixel() != 4 {
-			panic!("must have 4 bytes per pixel (RGBA) for file abs \"{}\"", file_name_abs);
+			return Err(VpbError::ImageFormat(
+				format!("must have 4 bytes per pixel (RGBA) for file abs \"{}\"", file_name_abs)
+			));
 		}
 		let image_size = vector![header_info.width, header_info.height];
 		drop(header_info);
-		let mut reader = decoder.read_info().unwrap();
+		let mut reader = decoder.read_info().map_err(
+			|error| VpbError::PngDecode(file_name_abs.to_string(), error)
+		)?;
 		let mut png_buffer = vec![0u8; reader.output_buffer_size()];
 		while let Ok(_) = reader.next_frame(&mut png_buffer) {}
-		(png_buffer, image_size)
+		Ok((png_buffer, image_size))
 	}
 
 	fn image_barrier(
@@ -137,60 +180,21 @@ impl GO_ImageArray {
 		command_buffer: &vk::CommandBuffer,
 		old_layout: vk::ImageLayout,
 		new_layout: vk::ImageLayout,
-	) { unsafe {
-		let (
-			src_access_mask,
-			dst_access_mask,
-			src_stage_mask,
-			dst_stage_mask,
-		 ) = match old_layout {
-			vk::ImageLayout::UNDEFINED => {
-				(
-					vk::AccessFlags::empty(),
-					vk::AccessFlags::TRANSFER_WRITE,
-					vk::PipelineStageFlags::TOP_OF_PIPE,
-					vk::PipelineStageFlags::TRANSFER,
-				)
-			},
-			vk::ImageLayout::TRANSFER_DST_OPTIMAL => {
-				(
-					vk::AccessFlags::TRANSFER_WRITE,
-					vk::AccessFlags::SHADER_READ,
-					vk::PipelineStageFlags::TRANSFER,
-					vk::PipelineStageFlags::VERTEX_SHADER,
-				)
-			},
-			_ => unimplemented!()
-		};
-		let memory_barrier = vk::ImageMemoryBarrier::builder()
-			.old_layout(old_layout)
-			.new_layout(new_layout)
-			.src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
-			.dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
-			.image(match &self.image_buffer.buffer {
+		ownership: QueueOwnership,
+	) {
+		upload_image_barrier(
+			device,
+			*command_buffer,
+			match &self.image_buffer.buffer {
 				crate::BufferType::Buffer(_) => { unreachable!(); },
 				crate::BufferType::Image(image) => { image.image },
-			})
-			.subresource_range(vk::ImageSubresourceRange::builder()
-				.aspect_mask(vk::ImageAspectFlags::COLOR)
-				.base_mip_level(0)
-				.level_count(1)
-				.base_array_layer(0)
-				.layer_count(self.image_layers)
-				.build())
-			.src_access_mask(src_access_mask)
-			.dst_access_mask(dst_access_mask)
-			.build();
-		device.device.cmd_pipeline_barrier(
-			*command_buffer,
-			src_stage_mask,
-			dst_stage_mask,
-			vk::DependencyFlags::empty(),
-			&[],
-			&[],
-			&[memory_barrier],
+			},
+			self.image_layers,
+			old_layout,
+			new_layout,
+			ownership,
 		);
-	}}
+	}
 
 	fn gpu_copy_image(
 		&self,
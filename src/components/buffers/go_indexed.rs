@@ -4,7 +4,7 @@ use std::mem::size_of;
 use ash::vk;
 use bytemuck::Pod;
 
-use crate::{Vertex, Device, VertexBuffer, BufferGO, Instance, IndexBuffer, RequirementType, program_data, ProgramData, BufferType};
+use crate::{Vertex, Device, VertexBuffer, BufferGO, Instance, IndexBuffer, RequirementType, program_data, ProgramData, BufferType, Result};
 
 #[allow(non_camel_case_types)]
 /// Vertex buffer with gpu only memory (nothing cached).
@@ -19,45 +19,53 @@ impl GO_Indexed {
 		program_data: &ProgramData,
 		vertices: &[V],
 		indices: &[u32],
-	) -> Self {
+	) -> Result<Self> {
 		let mut vb = BufferGO::new::<V>(
 			program_data,
 			RequirementType::Buffer(
 				size_of::<V>() * vertices.len(),
 				vk::BufferUsageFlags::VERTEX_BUFFER | vk::BufferUsageFlags::TRANSFER_DST,
 			),
-		);
-		vb.update(program_data, vertices);
+		)?;
+		vb.update(program_data, vertices)?;
 		let mut ib = BufferGO::new::<u32>(
 			program_data,
 			RequirementType::Buffer(
 				size_of::<u32>() * indices.len(),
 				vk::BufferUsageFlags::INDEX_BUFFER | vk::BufferUsageFlags::TRANSFER_DST,
 			),
-		);
-		ib.update(program_data, indices);
-		Self {
+		)?;
+		ib.update(program_data, indices)?;
+		Ok(Self {
 			vb,
 			ib,
 			index_count: indices.len(),
-		}
+		})
 	}
 
 	pub fn update_vertices<V: Default + Copy + Clone + Pod>(
 		&mut self,
 		program_data: &ProgramData,
 		vertices: &[V],
-	) {
-		self.vb.update(program_data, vertices);
+	) -> Result<()> {
+		self.vb.update(program_data, vertices)
 	}
 
 	pub fn update_indices(
 		&mut self,
 		program_data: &ProgramData,
 		indices: &[u32],
-	) {
+	) -> Result<()> {
 		self.index_count = indices.len();
-		self.ib.update(program_data, indices);
+		self.ib.update(program_data, indices)
+	}
+
+	pub fn destroy(
+		&self,
+		program_data: &ProgramData,
+	) -> Result<()> {
+		self.vb.destroy(program_data)?;
+		self.ib.destroy(program_data)
 	}
 }
 
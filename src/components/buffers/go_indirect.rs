@@ -3,7 +3,7 @@ use std::mem::size_of;
 use ash::vk;
 use bytemuck::{Pod, Zeroable};
 
-use crate::{Vertex, Device, VertexBuffer, IndexBuffer, BufferGO, Instance, RequirementType, ProgramData, BufferType};
+use crate::{Vertex, Device, VertexBuffer, IndexBuffer, BufferGO, Instance, RequirementType, ProgramData, BufferType, Result};
 
 #[repr(C)]
 #[derive(Zeroable, Pod, Default, Copy, Clone)]
@@ -31,64 +31,73 @@ impl GO_Indirect {
 		vertices: &[V],
 		indices: &[u32],
 		indirect: &[CrateDrawIndexedIndirectCommand],
-	) -> Self {
+	) -> Result<Self> {
 		let mut vb = BufferGO::new::<V>(
 			program_data,
 			RequirementType::Buffer(
 				size_of::<V>() * vertices.len(),
 				vk::BufferUsageFlags::VERTEX_BUFFER | vk::BufferUsageFlags::TRANSFER_DST,
 			),
-		);
-		vb.update(program_data, vertices);
+		)?;
+		vb.update(program_data, vertices)?;
 		let mut ib = BufferGO::new::<u32>(
 			program_data,
 			RequirementType::Buffer(
 				size_of::<u32>() * indices.len(),
 				vk::BufferUsageFlags::INDEX_BUFFER | vk::BufferUsageFlags::TRANSFER_DST,
 			),
-		);
-		ib.update(program_data, indices);
+		)?;
+		ib.update(program_data, indices)?;
 		let mut indirect_b = BufferGO::new::<CrateDrawIndexedIndirectCommand>(
 			program_data,
 			RequirementType::Buffer(
 				size_of::<u32>() * indices.len(),
 				vk::BufferUsageFlags::INDIRECT_BUFFER | vk::BufferUsageFlags::TRANSFER_DST,
 			),
-		);
-		indirect_b.update(program_data, indirect);
-		Self {
+		)?;
+		indirect_b.update(program_data, indirect)?;
+		Ok(Self {
 			index_count: indices.len(),
 			indirect_count: indirect.len(),
 			vb,
 			ib,
 			indirect: indirect_b,
-		}
+		})
 	}
 
 	pub fn update_vertices<V: Default + Copy + Clone + Pod>(
 		&mut self,
 		program_data: &ProgramData,
 		vertices: &[V],
-	) {
-		self.vb.update(program_data, vertices);
+	) -> Result<()> {
+		self.vb.update(program_data, vertices)
 	}
 
 	pub fn update_indices(
 		&mut self,
 		program_data: &ProgramData,
 		indices: &[u32],
-	) {
+	) -> Result<()> {
 		self.index_count = indices.len();
-		self.ib.update(program_data, indices);
+		self.ib.update(program_data, indices)
 	}
 
 	pub fn update_indirect(
 		&mut self,
 		program_data: &ProgramData,
 		indirect: &[CrateDrawIndexedIndirectCommand],
-	) {
+	) -> Result<()> {
 		self.indirect_count = indirect.len();
-		self.indirect.update(program_data, indirect);
+		self.indirect.update(program_data, indirect)
+	}
+
+	pub fn destroy(
+		&self,
+		program_data: &ProgramData,
+	) -> Result<()> {
+		self.vb.destroy(program_data)?;
+		self.ib.destroy(program_data)?;
+		self.indirect.destroy(program_data)
 	}
 }
 
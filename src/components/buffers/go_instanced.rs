@@ -3,7 +3,7 @@ use std::mem::size_of;
 use ash::vk;
 use bytemuck::Pod;
 
-use crate::{Vertex, Device, VertexBuffer, IndexBuffer, InstanceBuffer, BufferGO, Instance, RequirementType, ProgramData, BufferType};
+use crate::{Vertex, Device, VertexBuffer, IndexBuffer, InstanceBuffer, BufferGO, Instance, RequirementType, ProgramData, BufferType, Result};
 
 #[allow(non_camel_case_types)]
 /// Vertex, index, and indirect buffers with gpu only memory (nothing cached).
@@ -21,38 +21,38 @@ impl GO_Instanced {
 		vertices: &[V],
 		indices: &[u32],
 		instances: &[VI],
-	) -> Self {
+	) -> Result<Self> {
 		let mut vb = BufferGO::new::<V>(
 			program_data,
 			RequirementType::Buffer(
 				size_of::<V>() * vertices.len(),
 				vk::BufferUsageFlags::VERTEX_BUFFER | vk::BufferUsageFlags::TRANSFER_DST,
 			),
-		);
-		vb.update(program_data, vertices);
+		)?;
+		vb.update(program_data, vertices)?;
 		let mut ib = BufferGO::new::<u32>(
 			program_data,
 			RequirementType::Buffer(
 				size_of::<u32>() * indices.len(),
 				vk::BufferUsageFlags::INDEX_BUFFER | vk::BufferUsageFlags::TRANSFER_DST,
 			),
-		);
-		ib.update(program_data, indices);
+		)?;
+		ib.update(program_data, indices)?;
 		let mut instance_b = BufferGO::new::<VI>(
 			program_data,
 			RequirementType::Buffer(
 				size_of::<VI>() * instances.len(),
 				vk::BufferUsageFlags::VERTEX_BUFFER | vk::BufferUsageFlags::TRANSFER_DST,
 			),
-		);
-		instance_b.update(program_data, instances);
-		Self {
+		)?;
+		instance_b.update(program_data, instances)?;
+		Ok(Self {
 			index_count: indices.len(),
 			instance_count: instances.len(),
 			vb,
 			ib,
 			instance: instance_b,
-		}
+		})
 	}
 
 	pub fn with_capacity<V: Default + Copy + Clone + Pod, VI: Default + Copy + Clone + Pod>(
@@ -60,61 +60,70 @@ impl GO_Instanced {
 		vertex_capacity: usize,
 		index_capacity: usize,
 		instance_capacity: usize,
-	) -> Self {
+	) -> Result<Self> {
 		let mut vb = BufferGO::new::<V>(
 			program_data,
 			RequirementType::Buffer(
 				size_of::<V>() * vertex_capacity,
 				vk::BufferUsageFlags::VERTEX_BUFFER | vk::BufferUsageFlags::TRANSFER_DST,
 			),
-		);
+		)?;
 		let mut ib = BufferGO::new::<u32>(
 			program_data,
 			RequirementType::Buffer(
 				size_of::<u32>() * index_capacity,
 				vk::BufferUsageFlags::INDEX_BUFFER | vk::BufferUsageFlags::TRANSFER_DST,
 			),
-		);
+		)?;
 		let mut instance_b = BufferGO::new::<VI>(
 			program_data,
 			RequirementType::Buffer(
 				size_of::<VI>() * instance_capacity,
 				vk::BufferUsageFlags::VERTEX_BUFFER | vk::BufferUsageFlags::TRANSFER_DST,
 			),
-		);
-		Self {
+		)?;
+		Ok(Self {
 			index_count: 0,
 			instance_count: 0,
 			vb,
 			ib,
 			instance: instance_b,
-		}
+		})
 	}
 
 	pub fn update_vertices<V: Default + Copy + Clone + Pod>(
 		&mut self,
 		program_data: &ProgramData,
 		vertices: &[V],
-	) {
-		self.vb.update(program_data, vertices);
+	) -> Result<()> {
+		self.vb.update(program_data, vertices)
 	}
 
 	pub fn update_indices(
 		&mut self,
 		program_data: &ProgramData,
 		indices: &[u32],
-	) {
+	) -> Result<()> {
 		self.index_count = indices.len();
-		self.ib.update(program_data, indices);
+		self.ib.update(program_data, indices)
 	}
 
 	pub fn update_instances<VI: Default + Copy + Clone + Pod>(
 		&mut self,
 		program_data: &ProgramData,
 		instances: &[VI],
-	) {
+	) -> Result<()> {
 		self.instance_count = instances.len();
-		self.instance.update(program_data, instances);
+		self.instance.update(program_data, instances)
+	}
+
+	pub fn destroy(
+		&self,
+		program_data: &ProgramData,
+	) -> Result<()> {
+		self.vb.destroy(program_data)?;
+		self.ib.destroy(program_data)?;
+		self.instance.destroy(program_data)
 	}
 }
 
@@ -1,19 +1,19 @@
 use ash::vk;
 
-use crate::{CommandPool, Device, Swapchain};
+use crate::{CommandPool, Device, Result};
 
 pub struct CommandBuffer {
 	pub command_buffer: vk::CommandBuffer,
 	pub fence_submit: vk::Fence,
-	pub present_queue: vk::Queue,
+	/// Graphics queue.
+	pub queue: vk::Queue,
 }
 
 impl CommandBuffer {
 	pub fn new(
 		device: &Device,
 		command_pool: &CommandPool,
-		swapchain: &Swapchain,
-	) -> Self { unsafe {
+	) -> Result<Self> { unsafe {
 		let command_buffer_info = vk::CommandBufferAllocateInfo::builder()
 			.command_pool(command_pool.command_pool)
 			.command_buffer_count(1)
@@ -21,44 +21,58 @@ impl CommandBuffer {
 			.build();
 		let command_buffer = device.device.allocate_command_buffers(
 			&command_buffer_info
-		).unwrap()[0];
-		let present_queue = swapchain.present_queue;
+		)?[0];
+		let queue = device.queues.graphics.queue;
 		let fence_info = vk::FenceCreateInfo::builder()
 			.flags(vk::FenceCreateFlags::SIGNALED)
 			.build();
 		let fence_submit = device.device.create_fence(
 			&fence_info,
 			None,
-		).unwrap();
-		Self {
+		)?;
+		Ok(Self {
 			command_buffer,
-			present_queue,
+			queue,
 			fence_submit,
-		}
+		})
 	}}
 	pub fn open(
 		&self,
 		device: &Device,
-	) { unsafe {
+	) -> Result<()> { unsafe {
 		device.device.reset_command_buffer(
 			self.command_buffer,
 			vk::CommandBufferResetFlags::RELEASE_RESOURCES,
-		).unwrap();
+		)?;
 		let command_buffer_begin_info = vk::CommandBufferBeginInfo::builder()
 			.flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT)
 			.build();
 		device.device.begin_command_buffer(
 			self.command_buffer,
 			&command_buffer_begin_info,
-		).unwrap();
+		)?;
+		Ok(())
 	}}
 
 	pub fn close(
 		&self,
 		device: &Device,
-	) { unsafe {
+	) -> Result<()> { unsafe {
 		device.device.end_command_buffer(
 			self.command_buffer,
-		).unwrap();
+		)?;
+		Ok(())
+	}}
+
+	pub fn destroy(
+		&self,
+		device: &Device,
+		command_pool: &CommandPool,
+	) { unsafe {
+		device.device.free_command_buffers(
+			command_pool.command_pool,
+			&[self.command_buffer],
+		);
+		device.device.destroy_fence(self.fence_submit, None);
 	}}
 }
\ No newline at end of file
@@ -2,7 +2,7 @@ use std::marker::PhantomData;
 
 use ash::vk;
 
-use crate::{Device, CommandBuffer};
+use crate::{Device, CommandBuffer, Result};
 
 pub struct CommandPool {
 	pub command_pool: vk::CommandPool,
@@ -10,20 +10,39 @@ pub struct CommandPool {
 }
 
 impl CommandPool {
+	/// Pool for the graphics queue family.
 	pub fn new(
 		device: &Device,
-	) -> Self { unsafe {
+	) -> Result<Self> {
+		Self::for_family(
+			device,
+			device.queues.graphics.family_index,
+		)
+	}
+
+	pub fn for_family(
+		device: &Device,
+		queue_family_index: u32,
+	) -> Result<Self> { unsafe {
 		let command_pool_info = vk::CommandPoolCreateInfo::builder()
 			.flags(vk::CommandPoolCreateFlags::RESET_COMMAND_BUFFER)
-			.queue_family_index(device.queue_family_index[0])
+			.queue_family_index(queue_family_index)
 			.build();
 		let command_pool = device.device.create_command_pool(
 			&command_pool_info,
 			None,
-		).unwrap();
-		Self {
+		)?;
+		Ok(Self {
 			command_pool,
 			command_buffers: Vec::with_capacity(1024),
-		}
+		})
+	}}
+
+	/// Also frees every command buffer allocated from the pool.
+	pub fn destroy(
+		&self,
+		device: &Device,
+	) { unsafe {
+		device.device.destroy_command_pool(self.command_pool, None);
 	}}
 }
\ No newline at end of file
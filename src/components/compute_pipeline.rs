@@ -0,0 +1,388 @@
+use std::ffi::CStr;
+
+use ash::vk;
+use bytemuck::{Pod, bytes_of};
+
+use crate::{Device, ProgramData, Result, VpbError};
+
+/// Work that consumes what a dispatch wrote; see [`ComputePipeline::barrier`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ComputeReader {
+	/// A later dispatch reads or overwrites the results.
+	Compute,
+	/// Vertex, index or indirect draw buffers.
+	VertexInput,
+	/// Vertex or fragment shaders read storage buffers or images.
+	Graphics,
+	Transfer,
+	/// Mapped memory read on the CPU once the submission's fence is signaled.
+	Host,
+}
+
+impl ComputeReader {
+	fn stage_access(
+		&self,
+	) -> (vk::PipelineStageFlags, vk::AccessFlags) {
+		match self {
+			ComputeReader::Compute => (
+				vk::PipelineStageFlags::COMPUTE_SHADER,
+				vk::AccessFlags::SHADER_READ | vk::AccessFlags::SHADER_WRITE,
+			),
+			ComputeReader::VertexInput => (
+				vk::PipelineStageFlags::VERTEX_INPUT | vk::PipelineStageFlags::DRAW_INDIRECT,
+				vk::AccessFlags::VERTEX_ATTRIBUTE_READ | vk::AccessFlags::INDEX_READ | vk::AccessFlags::INDIRECT_COMMAND_READ,
+			),
+			ComputeReader::Graphics => (
+				vk::PipelineStageFlags::VERTEX_SHADER | vk::PipelineStageFlags::FRAGMENT_SHADER,
+				vk::AccessFlags::SHADER_READ,
+			),
+			ComputeReader::Transfer => (
+				vk::PipelineStageFlags::TRANSFER,
+				vk::AccessFlags::TRANSFER_READ,
+			),
+			ComputeReader::Host => (
+				vk::PipelineStageFlags::HOST,
+				vk::AccessFlags::HOST_READ,
+			),
+		}
+	}
+}
+
+#[derive(Clone, Debug)]
+pub struct ComputePipelineDesc {
+	pub shader_module: vk::ShaderModule,
+	/// Bindings of set 0, e.g. `STORAGE_BUFFER` or `STORAGE_IMAGE`; one descriptor each.
+	pub bindings: Vec<(u32, vk::DescriptorType)>,
+	/// Bytes of push constants visible to the compute stage.
+	pub push_constant_size: u32,
+}
+
+impl ComputePipelineDesc {
+	pub fn new(
+		shader_module: vk::ShaderModule,
+	) -> Self {
+		Self {
+			shader_module,
+			bindings: Vec::new(),
+			push_constant_size: 0,
+		}
+	}
+
+	pub fn binding(
+		mut self,
+		binding: u32,
+		descriptor_type: vk::DescriptorType,
+	) -> Self {
+		self.bindings.push((binding, descriptor_type));
+		self
+	}
+
+	pub fn push_constant_size(
+		mut self,
+		size: u32,
+	) -> Self {
+		self.push_constant_size = size;
+		self
+	}
+}
+
+/// Compute pipeline with its own set 0 layout and one descriptor set per frame in flight.
+pub struct ComputePipeline {
+	pub pipeline: vk::Pipeline,
+	pub layout: vk::PipelineLayout,
+	pub descriptor_set_layout: vk::DescriptorSetLayout,
+	pub descriptor_sets: Vec<vk::DescriptorSet>,
+	pub bindings: Vec<(u32, vk::DescriptorType)>,
+}
+
+impl ComputePipeline {
+	pub fn new(
+		program_data: &ProgramData,
+		desc: &ComputePipelineDesc,
+	) -> Result<Self> { unsafe {
+		let device = &program_data.device;
+		let layout_bindings: Vec<vk::DescriptorSetLayoutBinding> = desc.bindings.iter().map(
+			|(binding, descriptor_type)|
+			vk::DescriptorSetLayoutBinding::builder()
+				.binding(*binding)
+				.descriptor_type(*descriptor_type)
+				.descriptor_count(1)
+				.stage_flags(vk::ShaderStageFlags::COMPUTE)
+				.build()
+		).collect();
+		let descriptor_set_layout_info = vk::DescriptorSetLayoutCreateInfo::builder()
+			.bindings(layout_bindings.as_slice())
+			.build();
+		let descriptor_set_layout = device.device.create_descriptor_set_layout(
+			&descriptor_set_layout_info,
+			None,
+		)?;
+		let push_constant_ranges = [
+			vk::PushConstantRange::builder()
+				.stage_flags(vk::ShaderStageFlags::COMPUTE)
+				.offset(0)
+				.size(desc.push_constant_size)
+				.build(),
+		];
+		let layout_info = vk::PipelineLayoutCreateInfo::builder()
+			.set_layouts(std::slice::from_ref(&descriptor_set_layout))
+			.push_constant_ranges(match desc.push_constant_size {
+				0 => &push_constant_ranges[..0],
+				_ => &push_constant_ranges[..],
+			})
+			.build();
+		let layout = device.device.create_pipeline_layout(
+			&layout_info,
+			None,
+		)?;
+		let pipeline = match ComputePipeline::create_pipeline(
+			device,
+			desc.shader_module,
+			layout,
+		) {
+			Ok(pipeline) => pipeline,
+			Err(error) => {
+				device.device.destroy_pipeline_layout(layout, None);
+				device.device.destroy_descriptor_set_layout(descriptor_set_layout, None);
+				return Err(error);
+			},
+		};
+		let layouts = vec![
+			descriptor_set_layout;
+			program_data.frame_count
+		];
+		let allocate_info = vk::DescriptorSetAllocateInfo::builder()
+			.descriptor_pool(program_data.descriptor_pool.descriptor_pool)
+			.set_layouts(&layouts)
+			.build();
+		let descriptor_sets = match device.device.allocate_descriptor_sets(
+			&allocate_info,
+		) {
+			Ok(descriptor_sets) => descriptor_sets,
+			Err(error) => {
+				device.device.destroy_pipeline(pipeline, None);
+				device.device.destroy_pipeline_layout(layout, None);
+				device.device.destroy_descriptor_set_layout(descriptor_set_layout, None);
+				return Err(error.into());
+			},
+		};
+		Ok(Self {
+			pipeline,
+			layout,
+			descriptor_set_layout,
+			descriptor_sets,
+			bindings: desc.bindings.clone(),
+		})
+	}}
+
+	/// Pipeline only, for an existing layout; used to rebuild after the shader changes.
+	pub fn create_pipeline(
+		device: &Device,
+		shader_module: vk::ShaderModule,
+		layout: vk::PipelineLayout,
+	) -> Result<vk::Pipeline> { unsafe {
+		let name = CStr::from_bytes_with_nul_unchecked(b"main\0");
+		let stage_info = vk::PipelineShaderStageCreateInfo::builder()
+			.module(shader_module)
+			.name(name)
+			.stage(vk::ShaderStageFlags::COMPUTE)
+			.build();
+		let pipeline_info = vk::ComputePipelineCreateInfo::builder()
+			.stage(stage_info)
+			.layout(layout)
+			.build();
+		let pipelines = device.device.create_compute_pipelines(
+			vk::PipelineCache::null(),
+			&[pipeline_info],
+			None,
+		).map_err(|(_, error)| error)?;
+		Ok(pipelines[0])
+	}}
+
+	fn frame_sets(
+		&self,
+		frame: Option<usize>,
+	) -> &[vk::DescriptorSet] {
+		match frame {
+			Some(frame) => std::slice::from_ref(&self.descriptor_sets[frame]),
+			None => self.descriptor_sets.as_slice(),
+		}
+	}
+
+	fn descriptor_type(
+		&self,
+		binding: u32,
+	) -> Result<vk::DescriptorType> {
+		self.bindings.iter().find(|(index, _)| *index == binding).map(
+			|(_, descriptor_type)| *descriptor_type,
+		).ok_or_else(
+			|| VpbError::InvalidPipeline(format!("compute pipeline has no binding {}", binding))
+		)
+	}
+
+	/// Points `binding` of `frame`, or of every frame when `None`, at a buffer range.
+	/// The set must not be in use by a frame in flight. Fails with `InvalidPipeline` for an unknown binding.
+	pub fn write_buffer(
+		&self,
+		device: &Device,
+		frame: Option<usize>,
+		binding: u32,
+		buffer: vk::Buffer,
+		offset: vk::DeviceSize,
+		range: vk::DeviceSize,
+	) -> Result<()> { unsafe {
+		let descriptor_type = self.descriptor_type(binding)?;
+		let buffer_info = [
+			vk::DescriptorBufferInfo {
+				buffer,
+				offset,
+				range,
+			},
+		];
+		let writes: Vec<vk::WriteDescriptorSet> = self.frame_sets(frame).iter().map(
+			|descriptor_set|
+			vk::WriteDescriptorSet::builder()
+				.dst_set(*descriptor_set)
+				.dst_binding(binding)
+				.descriptor_type(descriptor_type)
+				.buffer_info(&buffer_info)
+				.build()
+		).collect();
+		device.device.update_descriptor_sets(
+			&writes,
+			&[] as &[vk::CopyDescriptorSet],
+		);
+		Ok(())
+	}}
+
+	/// Points `binding` at an image view; storage images are accessed in `GENERAL` layout.
+	pub fn write_image(
+		&self,
+		device: &Device,
+		frame: Option<usize>,
+		binding: u32,
+		image_view: vk::ImageView,
+		sampler: vk::Sampler,
+		image_layout: vk::ImageLayout,
+	) -> Result<()> { unsafe {
+		let descriptor_type = self.descriptor_type(binding)?;
+		let image_info = [
+			vk::DescriptorImageInfo {
+				sampler,
+				image_view,
+				image_layout,
+			},
+		];
+		let writes: Vec<vk::WriteDescriptorSet> = self.frame_sets(frame).iter().map(
+			|descriptor_set|
+			vk::WriteDescriptorSet::builder()
+				.dst_set(*descriptor_set)
+				.dst_binding(binding)
+				.descriptor_type(descriptor_type)
+				.image_info(&image_info)
+				.build()
+		).collect();
+		device.device.update_descriptor_sets(
+			&writes,
+			&[] as &[vk::CopyDescriptorSet],
+		);
+		Ok(())
+	}}
+
+	pub fn push_constants<T: Pod>(
+		&self,
+		device: &Device,
+		command_buffer: vk::CommandBuffer,
+		data: &T,
+	) { unsafe {
+		device.device.cmd_push_constants(
+			command_buffer,
+			self.layout,
+			vk::ShaderStageFlags::COMPUTE,
+			0,
+			bytes_of(data),
+		);
+	}}
+
+	/// Binds the pipeline and `frame`'s descriptor set, then dispatches `x * y * z` work groups.
+	pub fn dispatch(
+		&self,
+		device: &Device,
+		command_buffer: vk::CommandBuffer,
+		frame: usize,
+		x: u32,
+		y: u32,
+		z: u32,
+	) { unsafe {
+		device.device.cmd_bind_pipeline(
+			command_buffer,
+			vk::PipelineBindPoint::COMPUTE,
+			self.pipeline,
+		);
+		device.device.cmd_bind_descriptor_sets(
+			command_buffer,
+			vk::PipelineBindPoint::COMPUTE,
+			self.layout,
+			0,
+			&[self.descriptor_sets[frame]],
+			&[],
+		);
+		device.device.cmd_dispatch(
+			command_buffer,
+			x,
+			y,
+			z,
+		);
+	}}
+
+	/// Work groups needed to cover `size` invocations with `local_size` invocations per group.
+	/// Fails when `local_size` is zero.
+	pub fn group_count(
+		size: u32,
+		local_size: u32,
+	) -> Result<u32> {
+		if local_size == 0 {
+			return Err(VpbError::InvalidPipeline("compute local size must not be zero".to_string()));
+		}
+		Ok(size.div_ceil(local_size))
+	}
+
+	/// Makes shader writes of earlier dispatches visible to `reader`.
+	/// Covers buffers and images that stay in `GENERAL`; other image layouts need their own transition.
+	pub fn barrier(
+		device: &Device,
+		command_buffer: vk::CommandBuffer,
+		reader: ComputeReader,
+	) { unsafe {
+		let (dst_stage, dst_access) = reader.stage_access();
+		let memory_barrier = vk::MemoryBarrier::builder()
+			.src_access_mask(vk::AccessFlags::SHADER_WRITE)
+			.dst_access_mask(dst_access)
+			.build();
+		device.device.cmd_pipeline_barrier(
+			command_buffer,
+			vk::PipelineStageFlags::COMPUTE_SHADER,
+			dst_stage,
+			vk::DependencyFlags::empty(),
+			&[memory_barrier],
+			&[],
+			&[],
+		);
+	}}
+
+	/// The pipeline and its descriptor sets must no longer be in use by the device.
+	pub fn destroy(
+		&self,
+		program_data: &ProgramData,
+	) -> Result<()> { unsafe {
+		let device = &program_data.device;
+		device.device.free_descriptor_sets(
+			program_data.descriptor_pool.descriptor_pool,
+			&self.descriptor_sets,
+		)?;
+		device.device.destroy_pipeline(self.pipeline, None);
+		device.device.destroy_pipeline_layout(self.layout, None);
+		device.device.destroy_descriptor_set_layout(self.descriptor_set_layout, None);
+		Ok(())
+	}}
+}
\ No newline at end of file
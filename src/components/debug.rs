@@ -0,0 +1,181 @@
+use std::{ffi::CStr, borrow::Cow, sync::{Arc, atomic::{AtomicU64, Ordering}}};
+
+use ash::{vk, extensions::ext::DebugUtils};
+
+use crate::Result;
+
+pub struct DebugMessage<'a> {
+	pub severity: vk::DebugUtilsMessageSeverityFlagsEXT,
+	pub message_type: vk::DebugUtilsMessageTypeFlagsEXT,
+	pub id_name: &'a str,
+	pub id_number: i32,
+	pub message: &'a str,
+}
+
+pub type DebugSink = Box<dyn Fn(&DebugMessage) + Send + Sync>;
+
+pub struct DebugConfig {
+	/// Messages below this severity are not passed to the sink. Errors and warnings are always counted;
+	/// info and verbose messages are only received, and counted, when this is at or below them.
+	pub min_severity: vk::DebugUtilsMessageSeverityFlagsEXT,
+	/// Receives every message at or above `min_severity`; prints to stdout when `None`.
+	pub sink: Option<DebugSink>,
+}
+
+impl Default for DebugConfig {
+	fn default() -> Self {
+		Self {
+			min_severity: vk::DebugUtilsMessageSeverityFlagsEXT::WARNING,
+			sink: None,
+		}
+	}
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DebugCounts {
+	pub verbose: u64,
+	pub info: u64,
+	pub warning: u64,
+	pub error: u64,
+}
+
+struct DebugState {
+	min_severity: vk::DebugUtilsMessageSeverityFlagsEXT,
+	sink: DebugSink,
+	verbose: AtomicU64,
+	info: AtomicU64,
+	warning: AtomicU64,
+	error: AtomicU64,
+}
+
+pub struct DebugMessenger {
+	pub debug_utils: DebugUtils,
+	pub messenger: vk::DebugUtilsMessengerEXT,
+	state: Arc<DebugState>,
+}
+
+impl DebugMessenger {
+	pub fn new(
+		entry: &ash::Entry,
+		instance: &ash::Instance,
+		config: DebugConfig,
+	) -> Result<Self> { unsafe {
+		let state = Arc::new(DebugState {
+			min_severity: config.min_severity,
+			sink: config.sink.unwrap_or(Box::new(print_message)),
+			verbose: AtomicU64::new(0),
+			info: AtomicU64::new(0),
+			warning: AtomicU64::new(0),
+			error: AtomicU64::new(0),
+		});
+		// Warnings and errors are always subscribed to so they are counted.
+		let mut message_severity =
+			vk::DebugUtilsMessageSeverityFlagsEXT::ERROR |
+			vk::DebugUtilsMessageSeverityFlagsEXT::WARNING;
+		if config.min_severity.as_raw() <= vk::DebugUtilsMessageSeverityFlagsEXT::INFO.as_raw() {
+			message_severity |= vk::DebugUtilsMessageSeverityFlagsEXT::INFO;
+		}
+		if config.min_severity.as_raw() <= vk::DebugUtilsMessageSeverityFlagsEXT::VERBOSE.as_raw() {
+			message_severity |= vk::DebugUtilsMessageSeverityFlagsEXT::VERBOSE;
+		}
+		let debug_info = vk::DebugUtilsMessengerCreateInfoEXT::builder()
+			.message_severity(message_severity)
+			.message_type(
+				vk::DebugUtilsMessageTypeFlagsEXT::GENERAL |
+				vk::DebugUtilsMessageTypeFlagsEXT::VALIDATION |
+				vk::DebugUtilsMessageTypeFlagsEXT::PERFORMANCE,
+			)
+			.pfn_user_callback(Some(debug_callback))
+			.user_data(Arc::as_ptr(&state) as *mut std::os::raw::c_void)
+			.build();
+		let debug_utils = DebugUtils::new(entry, instance);
+		let messenger = debug_utils.create_debug_utils_messenger(
+			&debug_info,
+			None,
+		)?;
+		Ok(Self {
+			debug_utils,
+			messenger,
+			state,
+		})
+	}}
+
+	pub fn counts(
+		&self,
+	) -> DebugCounts {
+		DebugCounts {
+			verbose: self.state.verbose.load(Ordering::Relaxed),
+			info: self.state.info.load(Ordering::Relaxed),
+			warning: self.state.warning.load(Ordering::Relaxed),
+			error: self.state.error.load(Ordering::Relaxed),
+		}
+	}
+
+	pub fn destroy(
+		&self,
+	) { unsafe {
+		self.debug_utils.destroy_debug_utils_messenger(self.messenger, None);
+	}}
+
+	pub fn reset_counts(
+		&self,
+	) {
+		self.state.verbose.store(0, Ordering::Relaxed);
+		self.state.info.store(0, Ordering::Relaxed);
+		self.state.warning.store(0, Ordering::Relaxed);
+		self.state.error.store(0, Ordering::Relaxed);
+	}
+}
+
+fn print_message(
+	message: &DebugMessage,
+) {
+	println!(
+		"{:?}:\n{:?} [{} ({})] : {}\n",
+		message.severity,
+		message.message_type,
+		message.id_name,
+		message.id_number,
+		message.message,
+	);
+}
+
+unsafe extern "system" fn debug_callback(
+	message_severity: vk::DebugUtilsMessageSeverityFlagsEXT,
+	message_type: vk::DebugUtilsMessageTypeFlagsEXT,
+	p_callback_data: *const vk::DebugUtilsMessengerCallbackDataEXT,
+	user_data: *mut std::os::raw::c_void,
+) -> vk::Bool32 {
+	let state = &*(user_data as *const DebugState);
+	let counter = match message_severity {
+		vk::DebugUtilsMessageSeverityFlagsEXT::ERROR => &state.error,
+		vk::DebugUtilsMessageSeverityFlagsEXT::WARNING => &state.warning,
+		vk::DebugUtilsMessageSeverityFlagsEXT::INFO => &state.info,
+		_ => &state.verbose,
+	};
+	counter.fetch_add(1, Ordering::Relaxed);
+	if message_severity.as_raw() < state.min_severity.as_raw() {
+		return vk::FALSE;
+	}
+
+	let callback_data = *p_callback_data;
+	let message_id_name = if callback_data.p_message_id_name.is_null() {
+		Cow::from("")
+	} else {
+		CStr::from_ptr(callback_data.p_message_id_name).to_string_lossy()
+	};
+	let message = if callback_data.p_message.is_null() {
+		Cow::from("")
+	} else {
+		CStr::from_ptr(callback_data.p_message).to_string_lossy()
+	};
+	(state.sink)(&DebugMessage {
+		severity: message_severity,
+		message_type,
+		id_name: &message_id_name,
+		id_number: callback_data.message_id_number,
+		message: &message,
+	});
+
+	vk::FALSE
+}
\ No newline at end of file
@@ -0,0 +1,125 @@
+use std::sync::Mutex;
+
+use ash::vk;
+use vk_mem::Allocation;
+
+use crate::{Device, Result};
+
+/// GPU resource whose destruction is deferred by a [`DeletionQueue`].
+pub enum Deletion {
+	Buffer(vk::Buffer, Allocation),
+	Image(vk::Image, Allocation),
+	ImageView(vk::ImageView),
+	Sampler(vk::Sampler),
+	/// The pool must be created with `FREE_DESCRIPTOR_SET`.
+	DescriptorSets(vk::DescriptorPool, Vec<vk::DescriptorSet>),
+	Framebuffer(vk::Framebuffer),
+	/// Layouts are not included; they usually outlive a rebuilt pipeline.
+	Pipeline(vk::Pipeline),
+}
+
+impl Deletion {
+	pub fn destroy(
+		self,
+		device: &Device,
+		allocator: &vk_mem::Allocator,
+	) -> Result<()> { unsafe {
+		match self {
+			Deletion::Buffer(buffer, allocation) => {
+				allocator.destroy_buffer(buffer, &allocation)?;
+			},
+			Deletion::Image(image, allocation) => {
+				allocator.destroy_image(image, &allocation)?;
+			},
+			Deletion::ImageView(image_view) => {
+				device.device.destroy_image_view(image_view, None);
+			},
+			Deletion::Sampler(sampler) => {
+				device.device.destroy_sampler(sampler, None);
+			},
+			Deletion::DescriptorSets(descriptor_pool, descriptor_sets) => {
+				if !descriptor_sets.is_empty() {
+					device.device.free_descriptor_sets(descriptor_pool, &descriptor_sets)?;
+				}
+			},
+			Deletion::Framebuffer(framebuffer) => {
+				device.device.destroy_framebuffer(framebuffer, None);
+			},
+			Deletion::Pipeline(pipeline) => {
+				device.device.destroy_pipeline(pipeline, None);
+			},
+		}
+		Ok(())
+	}}
+}
+
+struct DeletionSlots {
+	/// Frame being recorded; new deletions wait for this frame.
+	current: usize,
+	slots: Vec<Vec<Deletion>>,
+}
+
+/// Defers destruction of resources until every frame that may still use them has finished.
+/// Deletions are grouped by the frame in flight that was current when they were pushed and are
+/// destroyed the next time that frame's fence has been waited on.
+pub struct DeletionQueue {
+	slots: Mutex<DeletionSlots>,
+}
+
+impl DeletionQueue {
+	pub fn new(
+		frame_count: usize,
+	) -> Self {
+		Self {
+			slots: Mutex::new(DeletionSlots {
+				current: 0,
+				slots: (0..frame_count.max(1)).map(|_| Vec::new()).collect(),
+			}),
+		}
+	}
+
+	pub fn push(
+		&self,
+		deletion: Deletion,
+	) {
+		let mut slots = self.slots.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+		let current = slots.current;
+		slots.slots[current].push(deletion);
+	}
+
+	/// Call once the fence of `frame_index` has been waited on, before recording into it.
+	/// Destroys what was deferred the last time `frame_index` was current and makes it current.
+	pub fn begin_frame(
+		&self,
+		frame_index: usize,
+		device: &Device,
+		allocator: &vk_mem::Allocator,
+	) -> Result<()> {
+		let deletions = {
+			let mut slots = self.slots.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+			let frame_index = frame_index % slots.slots.len();
+			slots.current = frame_index;
+			std::mem::take(&mut slots.slots[frame_index])
+		};
+		for deletion in deletions {
+			deletion.destroy(device, allocator)?;
+		}
+		Ok(())
+	}
+
+	/// Destroys everything; the device must be idle.
+	pub fn flush(
+		&self,
+		device: &Device,
+		allocator: &vk_mem::Allocator,
+	) -> Result<()> {
+		let deletions: Vec<Deletion> = {
+			let mut slots = self.slots.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+			slots.slots.iter_mut().flat_map(|slot| std::mem::take(slot)).collect()
+		};
+		for deletion in deletions {
+			deletion.destroy(device, allocator)?;
+		}
+		Ok(())
+	}
+}
\ No newline at end of file
@@ -2,7 +2,7 @@ use std::sync::Arc;
 
 use ash::vk;
 
-use crate::Device;
+use crate::{Device, Result};
 
 pub struct DescriptorPool {
     pub descriptor_pool: vk::DescriptorPool,
@@ -12,7 +12,7 @@ impl DescriptorPool {
 	pub fn new(
 		device: &Device,
 		frame_count: usize,
-	) -> Self { unsafe {
+	) -> Result<Self> { unsafe {
 		// TODO: Rust VMA
 		let descriptor_pool_max = 1024 * frame_count as u32;
 		let size_uniforms = vk::DescriptorPoolSize::builder()
@@ -23,16 +23,33 @@ impl DescriptorPool {
 			.ty(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
 			.descriptor_count(descriptor_pool_max)
 			.build();
+		let size_storage_buffers = vk::DescriptorPoolSize::builder()
+			.ty(vk::DescriptorType::STORAGE_BUFFER)
+			.descriptor_count(descriptor_pool_max)
+			.build();
+		let size_storage_images = vk::DescriptorPoolSize::builder()
+			.ty(vk::DescriptorType::STORAGE_IMAGE)
+			.descriptor_count(descriptor_pool_max)
+			.build();
 		let descriptor_pool_info = vk::DescriptorPoolCreateInfo::builder()
-			.pool_sizes(&[size_uniforms, size_textures])
+			.flags(vk::DescriptorPoolCreateFlags::FREE_DESCRIPTOR_SET)
+			.pool_sizes(&[size_uniforms, size_textures, size_storage_buffers, size_storage_images])
 			.max_sets(descriptor_pool_max)
 			.build();
 		let descriptor_pool = device.device.create_descriptor_pool(
 			&descriptor_pool_info,
 			None,
-		).unwrap();
-		Self {
+		)?;
+		Ok(Self {
 			descriptor_pool,
-		}
+		})
+	}}
+
+	/// Also frees every descriptor set allocated from the pool.
+	pub fn destroy(
+		&self,
+		device: &Device,
+	) { unsafe {
+		device.device.destroy_descriptor_pool(self.descriptor_pool, None);
 	}}
 }
\ No newline at end of file
@@ -1,105 +1,378 @@
-use std::ffi::c_char;
+use std::{ffi::{c_char, c_void, CStr, CString}, sync::{Mutex, MutexGuard}};
 
 use ash::{vk, extensions::khr};
 
-use crate::{Instance, Surface};
+use crate::{Instance, Surface, DeviceRequirements, DeviceFeatures, QueueFamilies, DeviceQueue, DeviceQueues, Result, VpbError, enumerate_device_extensions};
 
-const DEVICE_EXTENSIONS: [*const c_char; 1] = [
-	khr::Swapchain::name().as_ptr(),
-];
 const PRIORITIES: [f32; 1] = [
 	1.0,
 ];
 
+/// Overrides physical device selection; a number selects by index, anything else by name.
+pub const DEVICE_ENV_VAR: &str = "VPB_DEVICE";
+
+#[derive(Clone, Debug)]
+pub enum DeviceSelector {
+	/// Index into `enumerate_physical_devices`.
+	Index(usize),
+	/// Case insensitive substring of the device name.
+	Name(String),
+}
+
+impl DeviceSelector {
+	fn from_env(
+
+	) -> Option<Self> {
+		let value = std::env::var(DEVICE_ENV_VAR).ok()?;
+		match value.parse::<usize>() {
+			Ok(index) => Some(DeviceSelector::Index(index)),
+			Err(_) => Some(DeviceSelector::Name(value)),
+		}
+	}
+
+	fn matches(
+		&self,
+		index: usize,
+		name: &str,
+	) -> bool {
+		match self {
+			DeviceSelector::Index(selected) => *selected == index,
+			DeviceSelector::Name(selected) => name.to_lowercase().contains(&selected.to_lowercase()),
+		}
+	}
+}
+
+#[derive(Clone)]
+pub struct DeviceConfig {
+	/// Forces a specific physical device; `VPB_DEVICE` takes precedence when set.
+	pub selector: Option<DeviceSelector>,
+	pub requirements: DeviceRequirements,
+	/// First format usable as an optimal tiling depth attachment becomes `Device::depth_format`.
+	pub depth_formats: Vec<vk::Format>,
+}
+
+impl Default for DeviceConfig {
+	fn default() -> Self {
+		Self {
+			selector: None,
+			requirements: DeviceRequirements::default(),
+			depth_formats: vec![
+				vk::Format::D32_SFLOAT,
+				vk::Format::D24_UNORM_S8_UINT,
+				vk::Format::D32_SFLOAT_S8_UINT,
+				vk::Format::D16_UNORM,
+			],
+		}
+	}
+}
+
+/// True for depth formats that also carry a stencil aspect.
+pub fn format_has_stencil(
+	format: vk::Format,
+) -> bool {
+	matches!(
+		format,
+		vk::Format::S8_UINT |
+		vk::Format::D16_UNORM_S8_UINT |
+		vk::Format::D24_UNORM_S8_UINT |
+		vk::Format::D32_SFLOAT_S8_UINT
+	)
+}
+
+/// True for formats used as depth and/or stencil attachments.
+pub fn format_is_depth_stencil(
+	format: vk::Format,
+) -> bool {
+	format_has_stencil(format) || matches!(
+		format,
+		vk::Format::D16_UNORM |
+		vk::Format::X8_D24_UNORM_PACK32 |
+		vk::Format::D32_SFLOAT
+	)
+}
+
+struct DeviceCandidate {
+	index: usize,
+	physical_device: vk::PhysicalDevice,
+	properties: vk::PhysicalDeviceProperties,
+	name: String,
+	api_version: u32,
+	queue_families: Option<QueueFamilies>,
+	/// Features and extensions to enable; `None` when requirements are not met.
+	negotiated: Option<(DeviceFeatures, Vec<String>)>,
+}
+
+impl DeviceCandidate {
+	fn suitable(
+		&self,
+	) -> bool {
+		self.queue_families.is_some() && self.negotiated.is_some()
+	}
+
+	/// Discrete > integrated > virtual > cpu.
+	fn score(
+		&self,
+	) -> u32 {
+		match self.properties.device_type {
+			vk::PhysicalDeviceType::DISCRETE_GPU => 4,
+			vk::PhysicalDeviceType::INTEGRATED_GPU => 3,
+			vk::PhysicalDeviceType::VIRTUAL_GPU => 2,
+			vk::PhysicalDeviceType::CPU => 1,
+			_ => 0,
+		}
+	}
+}
+
 pub struct Device {
 	pub device: ash::Device,
 	pub physical_device: vk::PhysicalDevice,
-	pub queue_family_index: [u32; 1],
+	pub physical_device_properties: vk::PhysicalDeviceProperties,
+	pub name: String,
+	pub queue_families: QueueFamilies,
+	pub queues: DeviceQueues,
+	/// Lowest of the instance and device api versions.
+	pub api_version: u32,
+	pub enabled_features: DeviceFeatures,
+	pub enabled_extensions: Vec<String>,
 	pub surface_loader: khr::Surface,
 	pub physical_device_memory_properties: vk::PhysicalDeviceMemoryProperties,
-	pub surface_format: vk::SurfaceFormatKHR,
+	pub depth_format: vk::Format,
+	/// One per distinct family; roles sharing a family share its `vk::Queue`.
+	queue_locks: Vec<(u32, Mutex<()>)>,
 }
 
 impl Device {
+	/// Without a surface any graphics queue is accepted and no swapchain extension is enabled.
+	/// Dedicated compute and transfer queues are created when the device exposes such families.
 	pub fn new(
 		instance: &Instance,
-		surface: &Surface,
-	) -> Self { unsafe {
-		let features: vk::PhysicalDeviceFeatures =
-			vk::PhysicalDeviceFeatures {
-				shader_clip_distance: 1,
-				multi_draw_indirect: 1,
-				fill_mode_non_solid: 1,
-				..Default::default()
-			};
+		surface: Option<&Surface>,
+		config: &DeviceConfig,
+	) -> Result<Self> { unsafe {
+		let mut requirements = config.requirements.clone();
+		if surface.is_some() {
+			let swapchain_extension = khr::Swapchain::name().to_string_lossy().to_string();
+			if !requirements.required_extensions.contains(&swapchain_extension) {
+				requirements.required_extensions.push(swapchain_extension);
+			}
+		}
 		let pdevices =
 			instance.instance
-			.enumerate_physical_devices()
-			.expect("failed to retrive physical devices");
+			.enumerate_physical_devices()?;
 		let surface_loader = khr::Surface::new(
 			&instance.entry,
 			&instance.instance,
 		);
-		let (physical_device, queue_family_index) =
-			pdevices.iter().find_map(|pdevice| {
-				instance
-					.instance.get_physical_device_queue_family_properties(*pdevice)
-					.iter().enumerate().find_map(|(index, info)| {
-						let supports_graphic_and_surface =
-							info.queue_flags.contains(vk::QueueFlags::GRAPHICS) &&
-							surface_loader.get_physical_device_surface_support(
-								*pdevice,
-								index as u32,
-								surface.surface,
-							).unwrap();
-						if supports_graphic_and_surface {
-							Some((*pdevice, index))
-						} else {
-							None
-						}
-					})
-			}).expect("Couldn't find suitable device.");
+		let candidates: Vec<DeviceCandidate> = pdevices.iter().enumerate().map(|(index, pdevice)| {
+			let properties = instance.instance.get_physical_device_properties(*pdevice);
+			let api_version = properties.api_version.min(instance.api_version);
+			let supported_features = DeviceFeatures::query(
+				&instance.instance,
+				*pdevice,
+				api_version,
+			);
+			let available_extensions = enumerate_device_extensions(
+				&instance.instance,
+				*pdevice,
+			).unwrap_or_default();
+			let queue_families = QueueFamilies::find(
+				&instance.instance,
+				&surface_loader,
+				*pdevice,
+				surface,
+			);
+			DeviceCandidate {
+				index,
+				physical_device: *pdevice,
+				properties,
+				name: CStr::from_ptr(properties.device_name.as_ptr()).to_string_lossy().to_string(),
+				api_version,
+				queue_families,
+				negotiated: requirements.negotiate(
+					&supported_features,
+					&available_extensions,
+				),
+			}
+		}).collect();
+		let selector = DeviceSelector::from_env().or(config.selector.clone());
+		let candidate = match &selector {
+			Some(selector) => {
+				let candidate = candidates.iter().find(
+					|candidate|
+					selector.matches(candidate.index, &candidate.name)
+				).ok_or(VpbError::DeviceNotFound(format!("{:?}", selector)))?;
+				if !candidate.suitable() {
+					return Err(VpbError::DeviceNotFound(format!("{:?} ({} is not suitable)", selector, candidate.name)));
+				}
+				candidate
+			},
+			None => {
+				// `rev` so ties resolve to the first enumerated device.
+				candidates.iter().filter(
+					|candidate|
+					candidate.suitable()
+				).rev().max_by_key(
+					|candidate|
+					candidate.score()
+				).ok_or(VpbError::NoSuitableDevice)?
+			},
+		};
+		let physical_device = candidate.physical_device;
+		let physical_device_properties = candidate.properties;
+		let name = candidate.name.clone();
+		let api_version = candidate.api_version;
+		let queue_families = candidate.queue_families.unwrap_unchecked();
+		let (
+			mut enabled_features,
+			enabled_extensions,
+		) = candidate.negotiated.clone().unwrap_unchecked();
 		let physical_device_memory_properties = instance.instance.get_physical_device_memory_properties(
 			physical_device,
 		);
-		let queue_family_index = queue_family_index as u32;
-		let device_queue_info =
+		let depth_format = config.depth_formats.iter().copied().find(
+			|format|
+			instance.instance.get_physical_device_format_properties(
+				physical_device,
+				*format,
+			).optimal_tiling_features.contains(vk::FormatFeatureFlags::DEPTH_STENCIL_ATTACHMENT)
+		).ok_or(VpbError::NoSuitableDepthFormat)?;
+		let device_queue_infos: Vec<vk::DeviceQueueCreateInfo> = queue_families.unique().into_iter().map(
+			|queue_family_index|
 			vk::DeviceQueueCreateInfo::builder()
 			.queue_family_index(queue_family_index)
 			.queue_priorities(&PRIORITIES)
-			.build();
-		let device_info =
+			.build()
+		).collect();
+		let extension_names: Vec<CString> = enabled_extensions.iter().map(
+			|extension|
+			CString::new(extension.clone()).unwrap_unchecked()
+		).collect();
+		let extension_pointers: Vec<*const c_char> = extension_names.iter().map(
+			|extension|
+			extension.as_ptr()
+		).collect();
+		let features2 = enabled_features.chain(api_version);
+		let mut device_info =
 			vk::DeviceCreateInfo::builder()
-			.queue_create_infos(std::slice::from_ref(&device_queue_info))
-			.enabled_extension_names(&DEVICE_EXTENSIONS)
-			.enabled_features(&features)
+			.queue_create_infos(&device_queue_infos)
+			.enabled_extension_names(&extension_pointers)
 			.build();
+		if api_version >= vk::API_VERSION_1_1 {
+			device_info.p_next = &features2 as *const vk::PhysicalDeviceFeatures2 as *const c_void;
+		} else {
+			device_info.p_enabled_features = &enabled_features.vulkan_10;
+		}
 		let device =
 			instance.instance
 			.create_device(
 				physical_device,
 				&device_info,
 				None
-			).unwrap();
-		let surface_format = surface_loader.get_physical_device_surface_formats(
-			physical_device,
-			surface.surface,
-		).unwrap()[0];
-		Self {
+			)?;
+		enabled_features.unchain();
+		let queues = DeviceQueues::new(
+			&device,
+			&queue_families,
+		);
+		let queue_locks = queue_families.unique().into_iter().map(
+			|family_index|
+			(family_index, Mutex::new(()))
+		).collect();
+		Ok(Self {
 			device,
-			queue_family_index: [queue_family_index],
+			physical_device_properties,
+			name,
+			queue_families,
+			queues,
+			api_version,
+			enabled_features,
+			enabled_extensions,
 			surface_loader,
 			physical_device,
 			physical_device_memory_properties,
-			surface_format,
-		}
+			depth_format,
+			queue_locks,
+		})
+	}}
+
+	/// Externally synchronizes `queue`; hold the guard across every submit and present to it.
+	pub fn lock_queue(
+		&self,
+		queue: &DeviceQueue,
+	) -> Result<MutexGuard<'_, ()>> {
+		let (_, lock) = self.queue_locks.iter().find(
+			|(family_index, _)|
+			*family_index == queue.family_index
+		).ok_or(VpbError::InvalidQueue(queue.family_index))?;
+		Ok(lock.lock().unwrap_or_else(|poisoned| poisoned.into_inner()))
+	}
+
+	/// `vkDeviceWaitIdle` while holding every queue lock, as it synchronizes all queues.
+	pub fn wait_idle(
+		&self,
+	) -> Result<()> { unsafe {
+		let _locks: Vec<MutexGuard<'_, ()>> = self.queue_locks.iter().map(
+			|(_, lock)|
+			lock.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+		).collect();
+		self.device.device_wait_idle()?;
+		Ok(())
 	}}
 
+	/// `vkQueueSubmit` under [`Device::lock_queue`].
+	pub fn queue_submit(
+		&self,
+		queue: &DeviceQueue,
+		submits: &[vk::SubmitInfo],
+		fence: vk::Fence,
+	) -> Result<()> { unsafe {
+		let _lock = self.lock_queue(queue)?;
+		self.device.queue_submit(
+			queue.queue,
+			submits,
+			fence,
+		)?;
+		Ok(())
+	}}
+
+	/// Highest sample count not above `requested` that both color and depth framebuffers support.
+	pub fn clamp_samples(
+		&self,
+		requested: vk::SampleCountFlags,
+	) -> vk::SampleCountFlags {
+		let limits = &self.physical_device_properties.limits;
+		let supported = limits.framebuffer_color_sample_counts & limits.framebuffer_depth_sample_counts;
+		[
+			vk::SampleCountFlags::TYPE_64,
+			vk::SampleCountFlags::TYPE_32,
+			vk::SampleCountFlags::TYPE_16,
+			vk::SampleCountFlags::TYPE_8,
+			vk::SampleCountFlags::TYPE_4,
+			vk::SampleCountFlags::TYPE_2,
+		].into_iter().find(
+			|samples|
+			samples.as_raw() <= requested.as_raw() && supported.contains(*samples)
+		).unwrap_or(vk::SampleCountFlags::TYPE_1)
+	}
+
+	pub fn depth_has_stencil(
+		&self,
+	) -> bool {
+		format_has_stencil(self.depth_format)
+	}
+
+	pub fn has_extension(
+		&self,
+		name: &str,
+	) -> bool {
+		self.enabled_extensions.iter().any(|extension| extension == name)
+	}
+
 	pub fn find_memory_type_index(
 		memory_requirement: &vk::MemoryRequirements,
 		memory_properties: &vk::PhysicalDeviceMemoryProperties,
 		flags: vk::MemoryPropertyFlags,
-	) -> u32 {
+	) -> Result<u32> {
 		memory_properties.memory_types[..memory_properties.memory_type_count as _]
 			.iter().enumerate().find(
 				|(index, memory_type)| {
@@ -110,6 +383,13 @@ impl Device {
 				|(index, _)| {
 					index as _
 				}
-			).expect("failed to find memory index")
+			).ok_or(VpbError::NoSuitableMemoryType)
 	}
+
+	/// Every object created from the device must already be destroyed.
+	pub fn destroy(
+		&self,
+	) { unsafe {
+		self.device.destroy_device(None);
+	}}
 }
\ No newline at end of file
@@ -0,0 +1,358 @@
+use std::{mem::{offset_of, size_of}, slice, ffi::{CStr, c_void}, ptr};
+
+use ash::vk;
+
+use crate::Result;
+
+// Byte range of the Bool32 members (first and last) of each feature struct.
+const RANGE_10: (usize, usize) = (
+	offset_of!(vk::PhysicalDeviceFeatures, robust_buffer_access),
+	offset_of!(vk::PhysicalDeviceFeatures, inherited_queries),
+);
+const RANGE_11: (usize, usize) = (
+	offset_of!(vk::PhysicalDeviceVulkan11Features, storage_buffer16_bit_access),
+	offset_of!(vk::PhysicalDeviceVulkan11Features, shader_draw_parameters),
+);
+const RANGE_12: (usize, usize) = (
+	offset_of!(vk::PhysicalDeviceVulkan12Features, sampler_mirror_clamp_to_edge),
+	offset_of!(vk::PhysicalDeviceVulkan12Features, subgroup_broadcast_dynamic_id),
+);
+const RANGE_13: (usize, usize) = (
+	offset_of!(vk::PhysicalDeviceVulkan13Features, robust_image_access),
+	offset_of!(vk::PhysicalDeviceVulkan13Features, maintenance4),
+);
+
+/// Core features of every Vulkan version; `p_next` is always null outside of queries.
+#[derive(Clone, Copy, Default)]
+pub struct DeviceFeatures {
+	pub vulkan_10: vk::PhysicalDeviceFeatures,
+	pub vulkan_11: vk::PhysicalDeviceVulkan11Features,
+	pub vulkan_12: vk::PhysicalDeviceVulkan12Features,
+	pub vulkan_13: vk::PhysicalDeviceVulkan13Features,
+}
+
+unsafe impl Send for DeviceFeatures {}
+unsafe impl Sync for DeviceFeatures {}
+
+impl DeviceFeatures {
+	pub fn query(
+		instance: &ash::Instance,
+		physical_device: vk::PhysicalDevice,
+		api_version: u32,
+	) -> Self { unsafe {
+		let mut features = DeviceFeatures::default();
+		if api_version < vk::API_VERSION_1_1 {
+			features.vulkan_10 = instance.get_physical_device_features(physical_device);
+			return features;
+		}
+		let mut features2 = features.chain(api_version);
+		instance.get_physical_device_features2(
+			physical_device,
+			&mut features2,
+		);
+		features.vulkan_10 = features2.features;
+		features.unchain();
+		features
+	}}
+
+	/// Links the structs supported by `api_version` behind the returned `PhysicalDeviceFeatures2`.
+	/// `self` must not move while the returned struct is in use.
+	pub fn chain(
+		&mut self,
+		api_version: u32,
+	) -> vk::PhysicalDeviceFeatures2 {
+		let mut p_next: *mut c_void = ptr::null_mut();
+		if api_version >= vk::API_VERSION_1_3 {
+			self.vulkan_13.p_next = p_next;
+			p_next = &mut self.vulkan_13 as *mut _ as *mut c_void;
+		}
+		if api_version >= vk::API_VERSION_1_2 {
+			self.vulkan_12.p_next = p_next;
+			p_next = &mut self.vulkan_12 as *mut _ as *mut c_void;
+			self.vulkan_11.p_next = p_next;
+			p_next = &mut self.vulkan_11 as *mut _ as *mut c_void;
+		}
+		vk::PhysicalDeviceFeatures2 {
+			p_next,
+			features: self.vulkan_10,
+			..Default::default()
+		}
+	}
+
+	pub fn unchain(
+		&mut self,
+	) {
+		self.vulkan_11.p_next = ptr::null_mut();
+		self.vulkan_12.p_next = ptr::null_mut();
+		self.vulkan_13.p_next = ptr::null_mut();
+	}
+
+	/// True when every feature enabled in `self` is also enabled in `supported`.
+	pub fn is_subset_of(
+		&self,
+		supported: &DeviceFeatures,
+	) -> bool {
+		self.bools().iter().zip(supported.bools().iter()).all(
+			|(requested, supported)|
+			requested.iter().zip(supported.iter()).all(
+				|(requested, supported)|
+				*requested == vk::FALSE || *supported == vk::TRUE
+			)
+		)
+	}
+
+	pub fn intersection(
+		&self,
+		other: &DeviceFeatures,
+	) -> DeviceFeatures {
+		self.combine(other, |a, b| a == vk::TRUE && b == vk::TRUE)
+	}
+
+	pub fn union(
+		&self,
+		other: &DeviceFeatures,
+	) -> DeviceFeatures {
+		self.combine(other, |a, b| a == vk::TRUE || b == vk::TRUE)
+	}
+
+	fn combine(
+		&self,
+		other: &DeviceFeatures,
+		f: impl Fn(vk::Bool32, vk::Bool32) -> bool,
+	) -> DeviceFeatures {
+		let mut combined = DeviceFeatures::default();
+		let others = other.bools();
+		for (i, (target, this)) in combined.bools_mut().into_iter().zip(self.bools().iter()).enumerate() {
+			for (j, value) in target.iter_mut().enumerate() {
+				*value = f(this[j], others[i][j]) as vk::Bool32;
+			}
+		}
+		combined
+	}
+
+	fn bools(
+		&self,
+	) -> [&[vk::Bool32]; 4] { unsafe {
+		[
+			bool_range(&self.vulkan_10, RANGE_10),
+			bool_range(&self.vulkan_11, RANGE_11),
+			bool_range(&self.vulkan_12, RANGE_12),
+			bool_range(&self.vulkan_13, RANGE_13),
+		]
+	}}
+
+	fn bools_mut(
+		&mut self,
+	) -> [&mut [vk::Bool32]; 4] { unsafe {
+		[
+			bool_range_mut(&mut self.vulkan_10, RANGE_10),
+			bool_range_mut(&mut self.vulkan_11, RANGE_11),
+			bool_range_mut(&mut self.vulkan_12, RANGE_12),
+			bool_range_mut(&mut self.vulkan_13, RANGE_13),
+		]
+	}}
+}
+
+unsafe fn bool_range<T>(
+	features: &T,
+	range: (usize, usize),
+) -> &[vk::Bool32] {
+	slice::from_raw_parts(
+		(features as *const T as *const u8).add(range.0) as *const vk::Bool32,
+		(range.1 - range.0) / size_of::<vk::Bool32>() + 1,
+	)
+}
+
+unsafe fn bool_range_mut<T>(
+	features: &mut T,
+	range: (usize, usize),
+) -> &mut [vk::Bool32] {
+	slice::from_raw_parts_mut(
+		(features as *mut T as *mut u8).add(range.0) as *mut vk::Bool32,
+		(range.1 - range.0) / size_of::<vk::Bool32>() + 1,
+	)
+}
+
+#[derive(Clone)]
+pub struct DeviceRequirements {
+	/// Devices without all of these are never selected.
+	pub required_features: DeviceFeatures,
+	/// Enabled when supported; check `Device::enabled_features` before relying on them.
+	pub optional_features: DeviceFeatures,
+	pub required_extensions: Vec<String>,
+	pub optional_extensions: Vec<String>,
+}
+
+impl Default for DeviceRequirements {
+	fn default() -> Self {
+		let mut optional_features = DeviceFeatures::default();
+		optional_features.vulkan_10.shader_clip_distance = vk::TRUE;
+		optional_features.vulkan_10.multi_draw_indirect = vk::TRUE;
+		optional_features.vulkan_10.fill_mode_non_solid = vk::TRUE;
+		Self {
+			required_features: DeviceFeatures::default(),
+			optional_features,
+			required_extensions: Vec::new(),
+			optional_extensions: Vec::new(),
+		}
+	}
+}
+
+impl DeviceRequirements {
+	/// Features and extensions to enable, or `None` when something required is missing.
+	pub fn negotiate(
+		&self,
+		supported_features: &DeviceFeatures,
+		available_extensions: &[String],
+	) -> Option<(DeviceFeatures, Vec<String>)> {
+		if !self.required_features.is_subset_of(supported_features) {
+			return None;
+		}
+		if !self.required_extensions.iter().all(|extension| available_extensions.contains(extension)) {
+			return None;
+		}
+		let features = self.required_features.union(
+			&self.optional_features.intersection(supported_features),
+		);
+		let mut extensions = self.required_extensions.clone();
+		for extension in self.optional_extensions.iter() {
+			if available_extensions.contains(extension) && !extensions.contains(extension) {
+				extensions.push(extension.clone());
+			}
+		}
+		Some((features, extensions))
+	}
+}
+
+pub fn enumerate_device_extensions(
+	instance: &ash::Instance,
+	physical_device: vk::PhysicalDevice,
+) -> Result<Vec<String>> { unsafe {
+	Ok(instance.enumerate_device_extension_properties(
+		physical_device,
+	)?.iter().map(
+		|extension|
+		CStr::from_ptr(extension.extension_name.as_ptr()).to_string_lossy().to_string()
+	).collect())
+}}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn features(
+		set: impl Fn(&mut DeviceFeatures),
+	) -> DeviceFeatures {
+		let mut features = DeviceFeatures::default();
+		set(&mut features);
+		features
+	}
+
+	fn enabled(
+		features: &DeviceFeatures,
+	) -> usize {
+		features.bools().iter().map(
+			|bools|
+			bools.iter().filter(|value| **value == vk::TRUE).count()
+		).sum()
+	}
+
+	#[test]
+	fn ranges_cover_every_feature() {
+		let mut all = DeviceFeatures::default();
+		for bools in all.bools_mut() {
+			bools.fill(vk::TRUE);
+		}
+		let lengths: Vec<usize> = all.bools().iter().map(|bools| bools.len()).collect();
+		assert_eq!(lengths, vec![55, 12, 47, 15]);
+		assert_eq!(all.vulkan_10.robust_buffer_access, vk::TRUE);
+		assert_eq!(all.vulkan_10.inherited_queries, vk::TRUE);
+		assert_eq!(all.vulkan_11.storage_buffer16_bit_access, vk::TRUE);
+		assert_eq!(all.vulkan_11.shader_draw_parameters, vk::TRUE);
+		assert_eq!(all.vulkan_12.sampler_mirror_clamp_to_edge, vk::TRUE);
+		assert_eq!(all.vulkan_12.subgroup_broadcast_dynamic_id, vk::TRUE);
+		assert_eq!(all.vulkan_13.robust_image_access, vk::TRUE);
+		assert_eq!(all.vulkan_13.maintenance4, vk::TRUE);
+		// Headers in front of the ranges are left alone.
+		let defaults = DeviceFeatures::default();
+		assert_eq!(all.vulkan_11.s_type, defaults.vulkan_11.s_type);
+		assert_eq!(all.vulkan_12.s_type, defaults.vulkan_12.s_type);
+		assert_eq!(all.vulkan_13.s_type, defaults.vulkan_13.s_type);
+		assert!(all.vulkan_11.p_next.is_null());
+		assert!(all.vulkan_12.p_next.is_null());
+		assert!(all.vulkan_13.p_next.is_null());
+	}
+
+	#[test]
+	fn subset() {
+		let supported = features(|features| {
+			features.vulkan_10.multi_draw_indirect = vk::TRUE;
+			features.vulkan_12.descriptor_indexing = vk::TRUE;
+		});
+		let requested = features(|features| features.vulkan_12.descriptor_indexing = vk::TRUE);
+		assert!(requested.is_subset_of(&supported));
+		assert!(DeviceFeatures::default().is_subset_of(&supported));
+		assert!(!supported.is_subset_of(&requested));
+		let missing = features(|features| features.vulkan_13.dynamic_rendering = vk::TRUE);
+		assert!(!missing.is_subset_of(&supported));
+	}
+
+	#[test]
+	fn union_and_intersection() {
+		let a = features(|features| {
+			features.vulkan_10.fill_mode_non_solid = vk::TRUE;
+			features.vulkan_11.multiview = vk::TRUE;
+		});
+		let b = features(|features| {
+			features.vulkan_11.multiview = vk::TRUE;
+			features.vulkan_13.synchronization2 = vk::TRUE;
+		});
+		let union = a.union(&b);
+		assert_eq!(enabled(&union), 3);
+		assert_eq!(union.vulkan_10.fill_mode_non_solid, vk::TRUE);
+		assert_eq!(union.vulkan_11.multiview, vk::TRUE);
+		assert_eq!(union.vulkan_13.synchronization2, vk::TRUE);
+		let intersection = a.intersection(&b);
+		assert_eq!(enabled(&intersection), 1);
+		assert_eq!(intersection.vulkan_11.multiview, vk::TRUE);
+	}
+
+	#[test]
+	fn negotiate_required_missing() {
+		let requirements = DeviceRequirements {
+			required_features: features(|features| features.vulkan_13.dynamic_rendering = vk::TRUE),
+			..Default::default()
+		};
+		assert!(requirements.negotiate(&DeviceFeatures::default(), &[]).is_none());
+		let requirements = DeviceRequirements {
+			required_extensions: vec!["VK_KHR_swapchain".to_string()],
+			..Default::default()
+		};
+		assert!(requirements.negotiate(&DeviceFeatures::default(), &[]).is_none());
+	}
+
+	#[test]
+	fn negotiate_optional_missing() {
+		let requirements = DeviceRequirements {
+			required_features: features(|features| features.vulkan_12.timeline_semaphore = vk::TRUE),
+			optional_features: features(|features| {
+				features.vulkan_10.multi_draw_indirect = vk::TRUE;
+				features.vulkan_13.dynamic_rendering = vk::TRUE;
+			}),
+			required_extensions: vec!["VK_KHR_swapchain".to_string()],
+			optional_extensions: vec!["VK_EXT_memory_budget".to_string(), "VK_KHR_present_id".to_string()],
+		};
+		let supported = features(|features| {
+			features.vulkan_10.multi_draw_indirect = vk::TRUE;
+			features.vulkan_11.multiview = vk::TRUE;
+			features.vulkan_12.timeline_semaphore = vk::TRUE;
+		});
+		let available = vec!["VK_KHR_swapchain".to_string(), "VK_EXT_memory_budget".to_string()];
+		let (features, extensions) = requirements.negotiate(&supported, &available).unwrap();
+		assert_eq!(enabled(&features), 2);
+		assert_eq!(features.vulkan_10.multi_draw_indirect, vk::TRUE);
+		assert_eq!(features.vulkan_12.timeline_semaphore, vk::TRUE);
+		assert_eq!(features.vulkan_13.dynamic_rendering, vk::FALSE);
+		assert_eq!(extensions, available);
+	}
+}
\ No newline at end of file
@@ -1,6 +1,6 @@
 use ash::vk;
 
-use crate::Device;
+use crate::{Device, Result};
 
 pub struct Fence {
 	pub fence: vk::Fence,
@@ -9,13 +9,20 @@ pub struct Fence {
 impl Fence {
 	pub fn new(
 		device: &Device,
-	) -> Self { unsafe {
+	) -> Result<Self> { unsafe {
 		let fence_info = vk::FenceCreateInfo::builder()
 			.flags(vk::FenceCreateFlags::SIGNALED)
 			.build();
-		let fence = device.device.create_fence(&fence_info, None).unwrap();
-		Self {
+		let fence = device.device.create_fence(&fence_info, None)?;
+		Ok(Self {
 			fence,
-		}
+		})
+	}}
+
+	pub fn destroy(
+		&self,
+		device: &Device,
+	) { unsafe {
+		device.device.destroy_fence(self.fence, None);
 	}}
 }
\ No newline at end of file
@@ -0,0 +1,98 @@
+use ash::vk;
+
+use crate::{Device, RenderPass, PresentationImages, ProgramData, Result};
+
+/// One framebuffer per swapchain image, combining its view with the depth view for a render pass.
+pub struct Framebuffers {
+	pub framebuffers: Vec<vk::Framebuffer>,
+	pub extent: vk::Extent2D,
+	pub render_pass: vk::RenderPass,
+	/// `PresentationImages::generation` the framebuffers were built from.
+	pub generation: u64,
+}
+
+impl Framebuffers {
+	pub fn new(
+		device: &Device,
+		render_pass: &RenderPass,
+		presentation: &PresentationImages,
+	) -> Result<Self> {
+		let mut framebuffers = Self {
+			framebuffers: Vec::new(),
+			extent: presentation.extent,
+			render_pass: render_pass.render_pass,
+			generation: presentation.generation,
+		};
+		framebuffers.build(
+			device,
+			presentation,
+		)?;
+		Ok(framebuffers)
+	}
+
+	pub fn get(
+		&self,
+		image_index: u32,
+	) -> vk::Framebuffer {
+		self.framebuffers[image_index as usize]
+	}
+
+	/// Rebuilds when the swapchain was recreated since the last build; true when rebuilt.
+	/// The old framebuffers are destroyed, so the device must no longer use them.
+	pub fn update(
+		&mut self,
+		program_data: &ProgramData,
+	) -> Result<bool> {
+		let Some(state) = program_data.swapchain_state() else {
+			return Ok(false);
+		};
+		if state.presentation.generation == self.generation {
+			return Ok(false);
+		}
+		self.destroy(&program_data.device);
+		self.build(
+			&program_data.device,
+			&state.presentation,
+		)?;
+		Ok(true)
+	}
+
+	fn build(
+		&mut self,
+		device: &Device,
+		presentation: &PresentationImages,
+	) -> Result<()> { unsafe {
+		self.framebuffers.clear();
+		self.framebuffers.reserve(presentation.image_views.len());
+		for image_view in presentation.image_views.iter() {
+			let attachments = RenderPass::attachment_views(
+				*image_view,
+				presentation.depth.view,
+				presentation.multisampled_color.as_ref().map(|color| color.view),
+			);
+			let framebuffer_info = vk::FramebufferCreateInfo::builder()
+				.render_pass(self.render_pass)
+				.attachments(&attachments)
+				.width(presentation.extent.width)
+				.height(presentation.extent.height)
+				.layers(1)
+				.build();
+			self.framebuffers.push(device.device.create_framebuffer(
+				&framebuffer_info,
+				None,
+			)?);
+		}
+		self.extent = presentation.extent;
+		self.generation = presentation.generation;
+		Ok(())
+	}}
+
+	pub fn destroy(
+		&self,
+		device: &Device,
+	) { unsafe {
+		for framebuffer in self.framebuffers.iter() {
+			device.device.destroy_framebuffer(*framebuffer, None);
+		}
+	}}
+}
\ No newline at end of file
@@ -0,0 +1,310 @@
+use ash::vk;
+
+use crate::{Device, RenderPass, Vertex, BlockSpawnerGen, create_stage_infos, create_pipeline_layout, Result};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlendMode {
+	Opaque,
+	/// Straight alpha; `src * a + dst * (1 - a)`.
+	Alpha,
+	/// Color is already multiplied by alpha; `src + dst * (1 - a)`.
+	PremultipliedAlpha,
+	Additive,
+}
+
+impl BlendMode {
+	fn attachment_state(
+		&self,
+	) -> vk::PipelineColorBlendAttachmentState {
+		let (src_color, dst_color) = match self {
+			BlendMode::Opaque => (vk::BlendFactor::ONE, vk::BlendFactor::ZERO),
+			BlendMode::Alpha => (vk::BlendFactor::SRC_ALPHA, vk::BlendFactor::ONE_MINUS_SRC_ALPHA),
+			BlendMode::PremultipliedAlpha => (vk::BlendFactor::ONE, vk::BlendFactor::ONE_MINUS_SRC_ALPHA),
+			BlendMode::Additive => (vk::BlendFactor::SRC_ALPHA, vk::BlendFactor::ONE),
+		};
+		vk::PipelineColorBlendAttachmentState::builder()
+			.blend_enable(*self != BlendMode::Opaque)
+			.src_color_blend_factor(src_color)
+			.dst_color_blend_factor(dst_color)
+			.color_blend_op(vk::BlendOp::ADD)
+			.src_alpha_blend_factor(vk::BlendFactor::ONE)
+			.dst_alpha_blend_factor(match self {
+				BlendMode::Opaque => vk::BlendFactor::ZERO,
+				_ => vk::BlendFactor::ONE_MINUS_SRC_ALPHA,
+			})
+			.alpha_blend_op(vk::BlendOp::ADD)
+			.color_write_mask(vk::ColorComponentFlags::RGBA)
+			.build()
+	}
+}
+
+/// Graphics pipeline state; viewport and scissor are dynamic, see [`GraphicsPipeline::set_viewport`].
+#[derive(Clone, Debug)]
+pub struct GraphicsPipelineDesc {
+	pub shader_stages: Vec<(vk::ShaderModule, vk::ShaderStageFlags)>,
+	pub binding_descriptions: Vec<vk::VertexInputBindingDescription>,
+	pub attribute_descriptions: Vec<vk::VertexInputAttributeDescription>,
+	pub topology: vk::PrimitiveTopology,
+	pub polygon_mode: vk::PolygonMode,
+	pub cull_mode: vk::CullModeFlags,
+	pub front_face: vk::FrontFace,
+	pub line_width: f32,
+	pub depth_test: bool,
+	pub depth_write: bool,
+	pub depth_compare_op: vk::CompareOp,
+	/// Applied to every color attachment of `subpass`.
+	pub blend: BlendMode,
+	pub subpass: u32,
+	pub push_constant_ranges: Vec<vk::PushConstantRange>,
+}
+
+impl GraphicsPipelineDesc {
+	/// Opaque, back face culled triangle list with depth test and write, reading vertices of `V`.
+	pub fn new<V: Vertex>(
+	) -> Self {
+		Self {
+			shader_stages: Vec::new(),
+			binding_descriptions: V::binding_descriptions(),
+			attribute_descriptions: V::attribute_descriptions(),
+			topology: vk::PrimitiveTopology::TRIANGLE_LIST,
+			polygon_mode: vk::PolygonMode::FILL,
+			cull_mode: vk::CullModeFlags::BACK,
+			front_face: vk::FrontFace::COUNTER_CLOCKWISE,
+			line_width: 1.0,
+			depth_test: true,
+			depth_write: true,
+			depth_compare_op: vk::CompareOp::LESS_OR_EQUAL,
+			blend: BlendMode::Opaque,
+			subpass: 0,
+			push_constant_ranges: Vec::new(),
+		}
+	}
+
+	pub fn shader(
+		mut self,
+		shader_module: vk::ShaderModule,
+		stage: vk::ShaderStageFlags,
+	) -> Self {
+		self.shader_stages.push((shader_module, stage));
+		self
+	}
+
+	pub fn topology(
+		mut self,
+		topology: vk::PrimitiveTopology,
+	) -> Self {
+		self.topology = topology;
+		self
+	}
+
+	/// `LINE` and `POINT` need the `fill_mode_non_solid` feature.
+	pub fn polygon_mode(
+		mut self,
+		polygon_mode: vk::PolygonMode,
+	) -> Self {
+		self.polygon_mode = polygon_mode;
+		self
+	}
+
+	pub fn cull_mode(
+		mut self,
+		cull_mode: vk::CullModeFlags,
+		front_face: vk::FrontFace,
+	) -> Self {
+		self.cull_mode = cull_mode;
+		self.front_face = front_face;
+		self
+	}
+
+	pub fn depth(
+		mut self,
+		test: bool,
+		write: bool,
+	) -> Self {
+		self.depth_test = test;
+		self.depth_write = write;
+		self
+	}
+
+	pub fn blend(
+		mut self,
+		blend: BlendMode,
+	) -> Self {
+		self.blend = blend;
+		self
+	}
+
+	pub fn subpass(
+		mut self,
+		subpass: u32,
+	) -> Self {
+		self.subpass = subpass;
+		self
+	}
+
+	pub fn push_constants(
+		mut self,
+		range: vk::PushConstantRange,
+	) -> Self {
+		self.push_constant_ranges.push(range);
+		self
+	}
+
+	/// Rasterizes with the sample count and blends the color attachments of `subpass`. The layout takes the spawners' descriptor set layouts ordered by `SetId`.
+	pub fn build(
+		&self,
+		device: &Device,
+		render_pass: &RenderPass,
+		spawners: &[&dyn BlockSpawnerGen],
+	) -> Result<GraphicsPipeline> { unsafe {
+		let samples = render_pass.subpass_sample_count(self.subpass)?;
+		let color_attachment_count = render_pass.subpass_color_attachment_count(self.subpass)?;
+		let layout = create_pipeline_layout(
+			device,
+			spawners,
+			self.push_constant_ranges.as_slice(),
+		)?;
+		let pipeline = match self.create_pipeline(
+			device,
+			render_pass.render_pass,
+			samples,
+			color_attachment_count,
+			layout,
+		) {
+			Ok(pipeline) => pipeline,
+			Err(error) => {
+				device.device.destroy_pipeline_layout(layout, None);
+				return Err(error);
+			},
+		};
+		Ok(GraphicsPipeline {
+			pipeline,
+			layout,
+		})
+	}}
+
+	/// Pipeline only, for an existing layout; used to rebuild after shaders change.
+	pub fn create_pipeline(
+		&self,
+		device: &Device,
+		render_pass: vk::RenderPass,
+		samples: vk::SampleCountFlags,
+		color_attachment_count: u32,
+		layout: vk::PipelineLayout,
+	) -> Result<vk::Pipeline> { unsafe {
+		let stage_infos = create_stage_infos(self.shader_stages.as_slice());
+		let vertex_input_state = vk::PipelineVertexInputStateCreateInfo::builder()
+			.vertex_binding_descriptions(self.binding_descriptions.as_slice())
+			.vertex_attribute_descriptions(self.attribute_descriptions.as_slice())
+			.build();
+		let input_assembly_state = vk::PipelineInputAssemblyStateCreateInfo::builder()
+			.topology(self.topology)
+			.build();
+		let viewport_state = vk::PipelineViewportStateCreateInfo::builder()
+			.viewport_count(1)
+			.scissor_count(1)
+			.build();
+		let rasterization_state = vk::PipelineRasterizationStateCreateInfo::builder()
+			.polygon_mode(self.polygon_mode)
+			.cull_mode(self.cull_mode)
+			.front_face(self.front_face)
+			.line_width(self.line_width)
+			.build();
+		let multisample_state = vk::PipelineMultisampleStateCreateInfo::builder()
+			.rasterization_samples(samples)
+			.build();
+		let depth_stencil_state = vk::PipelineDepthStencilStateCreateInfo::builder()
+			.depth_test_enable(self.depth_test)
+			.depth_write_enable(self.depth_write)
+			.depth_compare_op(self.depth_compare_op)
+			.max_depth_bounds(1.0)
+			.build();
+		let blend_attachments = vec![
+			self.blend.attachment_state();
+			color_attachment_count as usize
+		];
+		let color_blend_state = vk::PipelineColorBlendStateCreateInfo::builder()
+			.attachments(blend_attachments.as_slice())
+			.build();
+		let dynamic_states = [
+			vk::DynamicState::VIEWPORT,
+			vk::DynamicState::SCISSOR,
+		];
+		let dynamic_state = vk::PipelineDynamicStateCreateInfo::builder()
+			.dynamic_states(&dynamic_states)
+			.build();
+		let pipeline_info = vk::GraphicsPipelineCreateInfo::builder()
+			.stages(stage_infos.as_slice())
+			.vertex_input_state(&vertex_input_state)
+			.input_assembly_state(&input_assembly_state)
+			.viewport_state(&viewport_state)
+			.rasterization_state(&rasterization_state)
+			.multisample_state(&multisample_state)
+			.depth_stencil_state(&depth_stencil_state)
+			.color_blend_state(&color_blend_state)
+			.dynamic_state(&dynamic_state)
+			.layout(layout)
+			.render_pass(render_pass)
+			.subpass(self.subpass)
+			.build();
+		let pipelines = device.device.create_graphics_pipelines(
+			vk::PipelineCache::null(),
+			&[pipeline_info],
+			None,
+		).map_err(|(_, error)| error)?;
+		Ok(pipelines[0])
+	}}
+}
+
+pub struct GraphicsPipeline {
+	pub pipeline: vk::Pipeline,
+	pub layout: vk::PipelineLayout,
+}
+
+impl GraphicsPipeline {
+	pub fn bind(
+		&self,
+		device: &Device,
+		command_buffer: vk::CommandBuffer,
+	) { unsafe {
+		device.device.cmd_bind_pipeline(
+			command_buffer,
+			vk::PipelineBindPoint::GRAPHICS,
+			self.pipeline,
+		);
+	}}
+
+	/// Sets the dynamic viewport and scissor to cover `extent`.
+	pub fn set_viewport(
+		device: &Device,
+		command_buffer: vk::CommandBuffer,
+		extent: vk::Extent2D,
+	) { unsafe {
+		device.device.cmd_set_viewport(
+			command_buffer,
+			0,
+			&[vk::Viewport {
+				x: 0.0,
+				y: 0.0,
+				width: extent.width as f32,
+				height: extent.height as f32,
+				min_depth: 0.0,
+				max_depth: 1.0,
+			}],
+		);
+		device.device.cmd_set_scissor(
+			command_buffer,
+			0,
+			&[extent.into()],
+		);
+	}}
+
+	/// The pipeline must no longer be in use by the device.
+	pub fn destroy(
+		&self,
+		device: &Device,
+	) { unsafe {
+		device.device.destroy_pipeline(self.pipeline, None);
+		device.device.destroy_pipeline_layout(self.layout, None);
+	}}
+}
\ No newline at end of file
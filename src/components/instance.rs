@@ -1,47 +1,125 @@
-use std::{ffi::{CStr, c_char}, borrow::Cow, marker::PhantomData};
+use std::{ffi::{CStr, CString, c_char}, marker::PhantomData};
 
-use ash::{vk::{self, DebugUtilsMessageSeverityFlagsEXT, InstanceCreateFlags}, extensions::{ext::DebugUtils, khr::Synchronization2}};
+use ash::{vk::{self, InstanceCreateFlags}, extensions::{ext::DebugUtils, khr::Synchronization2}};
 use raw_window_handle::HasRawDisplayHandle;
 
-use crate::Window;
+use crate::{Window, DebugConfig, DebugMessenger, DebugCounts, Result, VpbError};
+
+const VALIDATION_LAYER: &CStr = unsafe { CStr::from_bytes_with_nul_unchecked(
+	b"VK_LAYER_KHRONOS_validation\0",
+) };
+
+pub struct InstanceConfig {
+	/// Enables `VK_LAYER_KHRONOS_validation` and the debug messenger when they are available.
+	pub validation: bool,
+	/// Instance extensions enabled on top of the ones the window requires.
+	pub extensions: Vec<String>,
+	/// Enabled only when available; check `Instance::enabled_extensions`.
+	pub optional_extensions: Vec<String>,
+	pub api_version: u32,
+	pub debug: DebugConfig,
+}
+
+impl Default for InstanceConfig {
+	fn default() -> Self {
+		Self {
+			validation: cfg!(debug_assertions),
+			extensions: Vec::new(),
+			optional_extensions: Vec::new(),
+			api_version: vk::API_VERSION_1_3,
+			debug: DebugConfig::default(),
+		}
+	}
+}
 
 pub struct Instance {
 	pub entry: ash::Entry,
 	pub instance: ash::Instance,
-	pub debug_callback: vk::DebugUtilsMessengerEXT,
+	pub debug_messenger: Option<DebugMessenger>,
+	pub api_version: u32,
+	/// False when `InstanceConfig::validation` was set but the validation layer is not installed.
+	pub validation_enabled: bool,
+	pub enabled_extensions: Vec<String>,
 }
 
 impl Instance {
+	/// Without a window no surface extensions are enabled (headless).
 	pub fn new(
 		name: &str,
 		engine_name: &str,
-		window: &Window,
-	) -> Self { unsafe {
-		let layer_names = [
-			CStr::from_bytes_with_nul_unchecked(
-				b"VK_LAYER_KHRONOS_validation\0",
-			)
-		];
-		let layer_names_raw: Vec<*const c_char> = layer_names
+		window: Option<&Window>,
+		config: InstanceConfig,
+	) -> Result<Self> { unsafe {
+		let entry = ash::Entry::linked();
+		let available_layers: Vec<CString> = entry
+			.enumerate_instance_layer_properties()?
+			.iter().map(
+				|layer|
+				CStr::from_ptr(layer.layer_name.as_ptr()).to_owned()
+			).collect();
+		let available_extensions: Vec<CString> = entry
+			.enumerate_instance_extension_properties(None)?
 			.iter().map(
-				|raw_name|
-				raw_name.as_ptr()
+				|extension|
+				CStr::from_ptr(extension.extension_name.as_ptr()).to_owned()
 			).collect();
-		let required_extensions = window.glfw.get_required_instance_extensions().unwrap_or(vec![]);
-		assert!(required_extensions.contains(&"VK_KHR_surface".to_string()));
-		let extensions: Vec<std::ffi::CString> = required_extensions
+		let validation_enabled = config.validation &&
+			available_layers.iter().any(|layer| layer.as_c_str() == VALIDATION_LAYER);
+		let debug_utils_enabled = validation_enabled &&
+			available_extensions.iter().any(|extension| extension.as_c_str() == DebugUtils::name());
+		let mut layer_names_raw: Vec<*const c_char> = Vec::with_capacity(1);
+		if validation_enabled {
+			layer_names_raw.push(VALIDATION_LAYER.as_ptr());
+		}
+		let mut required_extensions = match window {
+			Some(window) => {
+				let required_extensions = window.glfw.get_required_instance_extensions().unwrap_or(vec![]);
+				if !required_extensions.contains(&"VK_KHR_surface".to_string()) {
+					return Err(VpbError::Window("glfw does not require VK_KHR_surface".to_string()));
+				}
+				required_extensions
+			},
+			None => vec![],
+		};
+		for extension in config.extensions.iter() {
+			if !required_extensions.contains(extension) {
+				required_extensions.push(extension.clone());
+			}
+		}
+		let mut extensions: Vec<CString> = required_extensions
 			.iter()
-			.map(|ext| std::ffi::CString::new(ext.clone()).expect("Failed to convert extension name"))
-			.collect();
-		let mut extension_pointers: Vec<*const i8> = extensions.iter().map(|ext| ext.as_ptr()).collect();
-		extension_pointers.push(DebugUtils::name().as_ptr());
-		let name_cstr = CStr::from_bytes_with_nul_unchecked(name.as_bytes());
-		let engine_name_cstr = CStr::from_bytes_with_nul_unchecked(engine_name.as_bytes());
-		let entry = ash::Entry::linked();
+			.map(|ext| CString::new(ext.clone()).map_err(
+				|_| VpbError::MissingInstanceExtension(ext.clone())
+			))
+			.collect::<Result<_>>()?;
+		if let Some(missing) = extensions.iter().find(
+			|extension|
+			!available_extensions.contains(extension)
+		) {
+			return Err(VpbError::MissingInstanceExtension(missing.to_string_lossy().to_string()));
+		}
+		for extension in config.optional_extensions.iter() {
+			let Ok(extension) = CString::new(extension.clone()) else {
+				continue;
+			};
+			if available_extensions.contains(&extension) && !extensions.contains(&extension) {
+				extensions.push(extension);
+			}
+		}
+		if debug_utils_enabled && !extensions.iter().any(|extension| extension.as_c_str() == DebugUtils::name()) {
+			extensions.push(DebugUtils::name().to_owned());
+		}
+		let extension_pointers: Vec<*const c_char> = extensions.iter().map(|ext| ext.as_ptr()).collect();
+		let name_cstr = CString::new(name).map_err(
+			|_| VpbError::InvalidName(name.to_string())
+		)?;
+		let engine_name_cstr = CString::new(engine_name).map_err(
+			|_| VpbError::InvalidName(engine_name.to_string())
+		)?;
 		let application_info = vk::ApplicationInfo {
 			p_application_name: name_cstr.as_ptr(),
 			p_engine_name: engine_name_cstr.as_ptr(),
-			api_version: vk::make_api_version(0, 1, 3, 0),
+			api_version: config.api_version,
 			..Default::default()
 		};
 		let instance_info = vk::InstanceCreateInfo::builder()
@@ -52,65 +130,46 @@ impl Instance {
 		let instance = entry.create_instance(
 			&instance_info,
 			None
-		).expect("failed to construct instance");
-		let debug_info = vk::DebugUtilsMessengerCreateInfoEXT::builder()
-			.message_severity(
-				vk::DebugUtilsMessageSeverityFlagsEXT::ERROR |
-				vk::DebugUtilsMessageSeverityFlagsEXT::WARNING |
-				vk::DebugUtilsMessageSeverityFlagsEXT::INFO,
-			)
-			.message_type(
-				vk::DebugUtilsMessageTypeFlagsEXT::GENERAL |
-				vk::DebugUtilsMessageTypeFlagsEXT::VALIDATION |
-				vk::DebugUtilsMessageTypeFlagsEXT::PERFORMANCE,
-			)
-			.pfn_user_callback(Some(debug_callback))
-			.build();
-		let debug_utils = DebugUtils::new(&entry, &instance);
-		let debug_callback = debug_utils
-			.create_debug_utils_messenger(
-				&debug_info,
-				None,
-		).unwrap();
-		Self {
+		)?;
+		let debug_messenger = if debug_utils_enabled {
+			Some(DebugMessenger::new(
+				&entry,
+				&instance,
+				config.debug,
+			)?)
+		} else {
+			None
+		};
+		Ok(Self {
 			entry,
 			instance,
-			debug_callback,
-		}
+			debug_messenger,
+			api_version: config.api_version,
+			validation_enabled,
+			enabled_extensions: extensions.iter().map(
+				|extension|
+				extension.to_string_lossy().to_string()
+			).collect(),
+		})
 	}}
-}
-
-unsafe extern "system" fn debug_callback(
-	message_severity: vk::DebugUtilsMessageSeverityFlagsEXT,
-	message_type: vk::DebugUtilsMessageTypeFlagsEXT,
-	p_callback_data: *const vk::DebugUtilsMessengerCallbackDataEXT,
-	_user_data: *mut std::os::raw::c_void,
-) -> vk::Bool32 {
-	let callback_data = *p_callback_data;
-	let message_id_number = callback_data.message_id_number;
-
-	let message_id_name = if callback_data.p_message_id_name.is_null() {
-		 Cow::from("")
-	} else {
-		 CStr::from_ptr(callback_data.p_message_id_name).to_string_lossy()
-	};
-
-	let message = if callback_data.p_message.is_null() {
-		 Cow::from("")
-	} else {
-		 CStr::from_ptr(callback_data.p_message).to_string_lossy()
-	};
 
-	println!(
-		 "{message_severity:?}:\n{message_type:?} [{message_id_name} ({message_id_number})] : {message}\n",
-	);
+	/// Destroys the debug messenger and the instance; surfaces and devices must already be destroyed.
+	pub fn destroy(
+		&self,
+	) { unsafe {
+		if let Some(debug_messenger) = &self.debug_messenger {
+			debug_messenger.destroy();
+		}
+		self.instance.destroy_instance(None);
+	}}
 
-	match message_severity {
-		DebugUtilsMessageSeverityFlagsEXT::ERROR => {
-			println!("E");
-		},
-		_ => {}
+	/// Messages reported by the validation layers so far; all zero when validation is disabled.
+	pub fn debug_counts(
+		&self,
+	) -> DebugCounts {
+		self.debug_messenger.as_ref().map_or(
+			DebugCounts::default(),
+			|debug_messenger| debug_messenger.counts(),
+		)
 	}
-
-	vk::FALSE
 }
\ No newline at end of file
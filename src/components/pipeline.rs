@@ -1,52 +1,177 @@
-use std::{fs::File, io::Read, sync::Arc, ffi::CStr, cell::RefCell};
+use std::{fs::File, io::Read, sync::{Arc, Mutex}, ffi::CStr, cell::RefCell, collections::HashMap, path::{Path, PathBuf}};
 
 use ash::{util::read_spv, vk::{self, ShaderModule, ShaderStageFlags}};
-use shaderc::{Compiler, CompileOptions, ShaderKind};
+use shaderc::{Compiler, CompileOptions, ShaderKind, ResolvedInclude};
 
-use crate::{Device, BlockState};
-
-// pub trait Pipeline {
-// 	fn get_viewport(&self) -> [vk::Viewport; 1];
-// 	fn get_scissor(&self) -> [vk::Rect2D; 1];
-	
-// 	fn destroy_block_state_memory(
-// 		&mut self,
-// 		device: &Arc<Device>,
-// 	);
-	
-// 	fn destroy_pipeline(
-// 		&mut self,
-// 		device: &Arc<Device>,
-// 	);
-
-// 	fn get_pipeline(&self) -> vk::Pipeline;
-// 	fn get_pipeline_layout(&self) -> vk::PipelineLayout;
-// 	fn get_block(&self) -> Arc<BlockState>;
-	
-// 	fn update_blocks(
-// 		&mut self,
-// 		device: &Device,
-// 		extent: &vk::Extent2D,
-// 		frame: usize,
-// 	);
-
-// 	fn object_binding_set(
-// 	) -> Vec<(u32, u32)> where Self: Sized;
-// }
+use crate::{Device, BlockState, BlockSpawnerGen, CachedShader, ShaderHash, Result, VpbError, SHADER_ROOT};
 
 pub struct ShaderLoader {
 	pub compiler: Compiler,
+	/// Keep compiled SPIR-V next to the source as `<source>.<variant>.spv` and reuse it while valid;
+	/// each set of defines gets its own file, which is overwritten when the source changes.
+	pub cache: bool,
+	/// Searched for `#include` after [`SHADER_ROOT`], in order.
+	pub search_paths: Vec<PathBuf>,
+	/// Files each compiled source included, directly or not, keyed by source path.
+	dependencies: Mutex<HashMap<String, Vec<String>>>,
+	/// Most recent failure to write a cache file, see [`ShaderLoader::take_cache_error`].
+	cache_error: Mutex<Option<VpbError>>,
 }
 
 impl ShaderLoader {
 	pub fn new(
-
-	) -> Self {
-		let compiler = Compiler::new().unwrap();
+		search_paths: Vec<PathBuf>,
+	) -> Result<Self> {
+		let compiler = Compiler::new().ok_or(VpbError::ShaderCompilerUnavailable)?;
 		let shader_loader = ShaderLoader {
 			compiler,
+			cache: true,
+			search_paths,
+			dependencies: Mutex::new(HashMap::new()),
+			cache_error: Mutex::new(None),
+		};
+		Ok(shader_loader)
+	}
+
+	/// First file named `requested` under the shader root or a search path.
+	pub fn resolve_include(
+		&self,
+		requested: &str,
+	) -> Option<PathBuf> {
+		std::iter::once(Path::new(SHADER_ROOT)).chain(
+			self.search_paths.iter().map(|search_path| search_path.as_path())
+		).map(
+			|directory|
+			directory.join(requested)
+		).find(
+			|path|
+			path.is_file()
+		)
+	}
+
+	/// Includes of `glsl_path` as of its last compile or cache hit.
+	pub fn dependencies(
+		&self,
+		glsl_path: &str,
+	) -> Vec<String> {
+		let dependencies = self.dependencies.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+		dependencies.get(glsl_path).cloned().unwrap_or_default()
+	}
+
+	/// Loaded sources that include `path`, i.e. those a change to `path` affects.
+	pub fn dependents(
+		&self,
+		path: &str,
+	) -> Vec<String> {
+		let dependencies = self.dependencies.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+		dependencies.iter().filter(
+			|(_, includes)|
+			includes.iter().any(|include| include == path)
+		).map(
+			|(glsl_path, _)|
+			glsl_path.clone()
+		).collect()
+	}
+
+	/// Takes the last error writing a cache file; compiling still succeeded when one occurred.
+	pub fn take_cache_error(
+		&self,
+	) -> Option<VpbError> {
+		self.cache_error.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).take()
+	}
+
+	fn set_dependencies(
+		&self,
+		glsl_path: &str,
+		includes: &[(String, ShaderHash)],
+	) {
+		let mut dependencies = self.dependencies.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+		dependencies.insert(
+			glsl_path.to_string(),
+			includes.iter().map(|(include, _)| include.clone()).collect(),
+		);
+	}
+
+	/// SPIR-V of the GLSL at `glsl_path`, from the cache when its key and includes still match.
+	/// `defines` are `#define name value`, or just `#define name` without a value.
+	pub fn compile(
+		&self,
+		shader_kind: ShaderKind,
+		glsl_path: &str,
+		defines: &[(&str, Option<&str>)],
+	) -> Result<Vec<u32>> {
+		let text = std::fs::read_to_string(glsl_path).map_err(
+			|error| VpbError::Io(glsl_path.to_string(), error)
+		)?;
+		// Everything but the source text names the file; the full key is checked against its contents.
+		let mut variant = ShaderHash::default()
+			.write(format!("{:?}", shader_kind).as_bytes())
+			.write(b"main");
+		for (name, value) in defines.iter() {
+			variant = variant
+				.write(name.as_bytes())
+				.write(value.unwrap_or_default().as_bytes());
+		}
+		for search_path in self.search_paths.iter() {
+			variant = variant.write(search_path.to_string_lossy().as_bytes());
+		}
+		let key = variant.write(text.as_bytes());
+		let spv_path = format!("{}.{:016x}.spv", glsl_path, variant.0);
+		if self.cache {
+			if let Some(cached) = CachedShader::read(&spv_path) {
+				if cached.is_valid(key) {
+					self.set_dependencies(glsl_path, &cached.dependencies);
+					return Ok(cached.spirv);
+				}
+			}
+		}
+		let includes: RefCell<Vec<(String, ShaderHash)>> = RefCell::new(Vec::new());
+		let binary_artifact = {
+			let mut options = CompileOptions::new().ok_or(VpbError::ShaderCompilerUnavailable)?;
+			for (name, value) in defines.iter() {
+				options.add_macro_definition(name, *value);
+			}
+			options.set_include_callback(|requested, _, requesting_source, _| {
+				let path = self.resolve_include(requested).ok_or(format!(
+					"\"{}\" included from \"{}\" is not in the shader root or a search path",
+					requested, requesting_source,
+				))?;
+				let resolved_name = path.to_string_lossy().to_string();
+				let content = std::fs::read_to_string(&path).map_err(
+					|error| format!("failed to read \"{}\": {}", resolved_name, error)
+				)?;
+				let mut includes = includes.borrow_mut();
+				if !includes.iter().any(|(include, _)| *include == resolved_name) {
+					includes.push((resolved_name.clone(), ShaderHash::of(content.as_bytes())));
+				}
+				Ok(ResolvedInclude {
+					resolved_name,
+					content,
+				})
+			});
+			self.compiler.compile_into_spirv(
+				text.as_str(),
+				shader_kind,
+				glsl_path, "main",
+				Some(&options),
+			).map_err(
+				|error| VpbError::ShaderCompile(glsl_path.to_string(), error)
+			)?
+		};
+		debug_assert_eq!(Some(&0x07230203), binary_artifact.as_binary().first());
+		let cached = CachedShader {
+			key,
+			dependencies: includes.into_inner(),
+			spirv: binary_artifact.as_binary().to_vec(),
 		};
-		shader_loader
+		self.set_dependencies(glsl_path, &cached.dependencies);
+		if self.cache {
+			// A cache that cannot be written only costs the next launch a recompile.
+			if let Err(error) = cached.write(&spv_path) {
+				*self.cache_error.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(error);
+			}
+		}
+		Ok(cached.spirv)
 	}
 }
 
@@ -62,4 +187,34 @@ pub fn create_stage_infos(
 			.stage(*shader_stage)
 			.build()
 	).collect()
+}}
+
+/// Layout whose descriptor sets are the spawners' layouts ordered by `SetId`; sets must be numbered from 0 without gaps.
+pub fn create_pipeline_layout(
+	device: &Device,
+	spawners: &[&dyn BlockSpawnerGen],
+	push_constant_ranges: &[vk::PushConstantRange],
+) -> Result<vk::PipelineLayout> { unsafe {
+	let mut sets: Vec<_> = spawners.iter().map(
+		|spawner|
+		(spawner.set(), spawner.layout())
+	).collect();
+	sets.sort_by_key(|(set, _)| *set);
+	for (index, (set, _)) in sets.iter().enumerate() {
+		if set.0 != index as u32 {
+			return Err(VpbError::InvalidPipeline(format!(
+				"descriptor set {} is missing or bound twice; found set {}",
+				index, set.0,
+			)));
+		}
+	}
+	let set_layouts: Vec<vk::DescriptorSetLayout> = sets.into_iter().map(|(_, layout)| layout).collect();
+	let layout_info = vk::PipelineLayoutCreateInfo::builder()
+		.set_layouts(set_layouts.as_slice())
+		.push_constant_ranges(push_constant_ranges)
+		.build();
+	Ok(device.device.create_pipeline_layout(
+		&layout_info,
+		None,
+	)?)
 }}
\ No newline at end of file
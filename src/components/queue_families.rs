@@ -0,0 +1,255 @@
+use ash::{vk, extensions::khr};
+
+use crate::{Device, Surface};
+
+/// Queue family chosen for each role; roles share a family when no dedicated one exists.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QueueFamilies {
+	pub graphics: u32,
+	pub present: u32,
+	/// Compute only family when available, otherwise `graphics`.
+	pub compute: u32,
+	/// Transfer only family when available, otherwise `compute`.
+	pub transfer: u32,
+}
+
+impl QueueFamilies {
+	/// `None` when there is no graphics family, or no family can present to `surface`.
+	pub fn find(
+		instance: &ash::Instance,
+		surface_loader: &khr::Surface,
+		physical_device: vk::PhysicalDevice,
+		surface: Option<&Surface>,
+	) -> Option<Self> { unsafe {
+		let families = instance.get_physical_device_queue_family_properties(physical_device);
+		let supports_present = |index: usize| {
+			surface.map_or(true, |surface| {
+				surface_loader.get_physical_device_surface_support(
+					physical_device,
+					index as u32,
+					surface.surface,
+				).unwrap_or(false)
+			})
+		};
+		let find = |predicate: &dyn Fn(usize, vk::QueueFlags) -> bool| {
+			families.iter().enumerate().find(
+				|(index, info)|
+				info.queue_count > 0 && predicate(*index, info.queue_flags)
+			).map(
+				|(index, _)|
+				index as u32
+			)
+		};
+		let graphics = find(&|index, flags| flags.contains(vk::QueueFlags::GRAPHICS) && supports_present(index))
+			.or_else(|| find(&|_, flags| flags.contains(vk::QueueFlags::GRAPHICS)))?;
+		let present = if supports_present(graphics as usize) {
+			graphics
+		} else {
+			find(&|index, _| supports_present(index))?
+		};
+		let compute = find(&|_, flags| flags.contains(vk::QueueFlags::COMPUTE) && !flags.contains(vk::QueueFlags::GRAPHICS))
+			.unwrap_or(graphics);
+		let transfer = find(&|_, flags| {
+			flags.contains(vk::QueueFlags::TRANSFER) &&
+			!flags.intersects(vk::QueueFlags::GRAPHICS | vk::QueueFlags::COMPUTE)
+		}).unwrap_or(compute);
+		Some(Self {
+			graphics,
+			present,
+			compute,
+			transfer,
+		})
+	}}
+
+	/// Distinct family indices, graphics first.
+	pub fn unique(
+		&self,
+	) -> Vec<u32> {
+		let mut indices = Vec::with_capacity(4);
+		for index in [self.graphics, self.present, self.compute, self.transfer] {
+			if !indices.contains(&index) {
+				indices.push(index);
+			}
+		}
+		indices
+	}
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct DeviceQueue {
+	pub family_index: u32,
+	pub queue: vk::Queue,
+}
+
+/// Roles that share a family also share the `vk::Queue`; submit through [`crate::Device::queue_submit`].
+#[derive(Clone, Copy, Debug)]
+pub struct DeviceQueues {
+	pub graphics: DeviceQueue,
+	pub present: DeviceQueue,
+	/// Not submitted to by vpb; for applications running async compute through `Device::queue_submit`.
+	pub compute: DeviceQueue,
+	/// Uploads through `ProgramData::immediate_transfer`.
+	pub transfer: DeviceQueue,
+}
+
+impl DeviceQueues {
+	/// Fetches queue 0 of every family in `families`; each must have been requested at device creation.
+	pub fn new(
+		device: &ash::Device,
+		families: &QueueFamilies,
+	) -> Self { unsafe {
+		let get = |family_index: u32| DeviceQueue {
+			family_index,
+			queue: device.get_device_queue(
+				family_index,
+				0,
+			),
+		};
+		Self {
+			graphics: get(families.graphics),
+			present: get(families.present),
+			compute: get(families.compute),
+			transfer: get(families.transfer),
+		}
+	}}
+
+	pub fn has_async_compute(
+		&self,
+	) -> bool {
+		self.compute.family_index != self.graphics.family_index
+	}
+
+	/// True when uploads can run on a different family than rendering.
+	pub fn has_dedicated_transfer(
+		&self,
+	) -> bool {
+		self.transfer.family_index != self.graphics.family_index
+	}
+
+	/// Release half of the transfer to graphics handover of uploaded resources.
+	pub fn upload_release(
+		&self,
+	) -> QueueOwnership {
+		match self.has_dedicated_transfer() {
+			true => QueueOwnership::Release(self.transfer.family_index, self.graphics.family_index),
+			false => QueueOwnership::Keep,
+		}
+	}
+
+	/// Acquire half matching [`DeviceQueues::upload_release`].
+	pub fn upload_acquire(
+		&self,
+	) -> QueueOwnership {
+		match self.has_dedicated_transfer() {
+			true => QueueOwnership::Acquire(self.transfer.family_index, self.graphics.family_index),
+			false => QueueOwnership::Keep,
+		}
+	}
+}
+
+/// Side of a queue family ownership transfer that a barrier is recorded on.
+/// Exclusive resources written on one family must be released there and acquired on the other before use.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QueueOwnership {
+	/// Used by a single family; nothing is transferred.
+	Keep,
+	/// Recorded on the source queue; source and destination family.
+	Release(u32, u32),
+	/// Recorded on the destination queue with the same families as the release.
+	Acquire(u32, u32),
+}
+
+impl QueueOwnership {
+	/// Source and destination family index of a barrier.
+	pub fn family_indices(
+		&self,
+	) -> (u32, u32) {
+		match self {
+			QueueOwnership::Keep => (vk::QUEUE_FAMILY_IGNORED, vk::QUEUE_FAMILY_IGNORED),
+			QueueOwnership::Release(src, dst) | QueueOwnership::Acquire(src, dst) => (*src, *dst),
+		}
+	}
+}
+
+/// Transition of the color layers `0..layer_count` of an uploaded image. Masks follow `old_layout`
+/// and which side of `ownership` is recorded; the layout change is repeated on both sides of a transfer.
+pub fn upload_image_barrier(
+	device: &Device,
+	command_buffer: vk::CommandBuffer,
+	image: vk::Image,
+	layer_count: u32,
+	old_layout: vk::ImageLayout,
+	new_layout: vk::ImageLayout,
+	ownership: QueueOwnership,
+) { unsafe {
+	let (
+		src_access_mask,
+		dst_access_mask,
+		src_stage_mask,
+		dst_stage_mask,
+	 ) = match (old_layout, ownership) {
+		(vk::ImageLayout::UNDEFINED, _) => {
+			(
+				vk::AccessFlags::empty(),
+				vk::AccessFlags::TRANSFER_WRITE,
+				vk::PipelineStageFlags::TOP_OF_PIPE,
+				vk::PipelineStageFlags::TRANSFER,
+			)
+		},
+		(vk::ImageLayout::TRANSFER_DST_OPTIMAL, QueueOwnership::Keep) => {
+			(
+				vk::AccessFlags::TRANSFER_WRITE,
+				vk::AccessFlags::SHADER_READ,
+				vk::PipelineStageFlags::TRANSFER,
+				vk::PipelineStageFlags::VERTEX_SHADER,
+			)
+		},
+		// Destination access is ignored on release and source access on acquire.
+		(vk::ImageLayout::TRANSFER_DST_OPTIMAL, QueueOwnership::Release(_, _)) => {
+			(
+				vk::AccessFlags::TRANSFER_WRITE,
+				vk::AccessFlags::empty(),
+				vk::PipelineStageFlags::TRANSFER,
+				vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+			)
+		},
+		(vk::ImageLayout::TRANSFER_DST_OPTIMAL, QueueOwnership::Acquire(_, _)) => {
+			(
+				vk::AccessFlags::empty(),
+				vk::AccessFlags::SHADER_READ,
+				vk::PipelineStageFlags::TOP_OF_PIPE,
+				vk::PipelineStageFlags::VERTEX_SHADER,
+			)
+		},
+		_ => unimplemented!()
+	};
+	let (
+		src_queue_family_index,
+		dst_queue_family_index,
+	) = ownership.family_indices();
+	let memory_barrier = vk::ImageMemoryBarrier::builder()
+		.old_layout(old_layout)
+		.new_layout(new_layout)
+		.src_queue_family_index(src_queue_family_index)
+		.dst_queue_family_index(dst_queue_family_index)
+		.image(image)
+		.subresource_range(vk::ImageSubresourceRange::builder()
+			.aspect_mask(vk::ImageAspectFlags::COLOR)
+			.base_mip_level(0)
+			.level_count(1)
+			.base_array_layer(0)
+			.layer_count(layer_count)
+			.build())
+		.src_access_mask(src_access_mask)
+		.dst_access_mask(dst_access_mask)
+		.build();
+	device.device.cmd_pipeline_barrier(
+		command_buffer,
+		src_stage_mask,
+		dst_stage_mask,
+		vk::DependencyFlags::empty(),
+		&[],
+		&[],
+		&[memory_barrier],
+	);
+}}
\ No newline at end of file
@@ -1,68 +1,148 @@
 use ash::vk;
 
-use crate::{Swapchain, Device, CommandBuffer};
+use crate::{Device, CommandBuffer, RenderPassDesc, Result, VpbError};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AttachmentOps {
+	pub load_op: vk::AttachmentLoadOp,
+	pub store_op: vk::AttachmentStoreOp,
+}
+
+impl AttachmentOps {
+	pub const CLEAR: Self = Self {
+		load_op: vk::AttachmentLoadOp::CLEAR,
+		store_op: vk::AttachmentStoreOp::STORE,
+	};
+	/// Keeps what an earlier pass rendered, e.g. to draw UI over the scene.
+	pub const LOAD: Self = Self {
+		load_op: vk::AttachmentLoadOp::LOAD,
+		store_op: vk::AttachmentStoreOp::STORE,
+	};
+	/// Contents are neither read nor kept.
+	pub const DONT_CARE: Self = Self {
+		load_op: vk::AttachmentLoadOp::DONT_CARE,
+		store_op: vk::AttachmentStoreOp::DONT_CARE,
+	};
+}
+
+#[derive(Clone, Debug)]
+pub struct RenderPassConfig {
+	pub clear_color: [f32; 4],
+	pub clear_depth: f32,
+	pub clear_stencil: u32,
+	/// When multisampling only `load_op` applies to the multisampled image, which is never stored;
+	/// the resolve target is always stored.
+	pub color: AttachmentOps,
+	/// Also used for stencil when the depth format has one.
+	pub depth: AttachmentOps,
+}
+
+impl Default for RenderPassConfig {
+	fn default() -> Self {
+		Self {
+			clear_color: [0.0, 0.0, 0.0, 1.0],
+			clear_depth: 1.0,
+			clear_stencil: 0,
+			color: AttachmentOps::CLEAR,
+			depth: AttachmentOps::CLEAR,
+		}
+	}
+}
+
+impl RenderPassConfig {
+	/// Draws over the previous pass's color and depth without clearing them.
+	pub fn overlay(
+	) -> Self {
+		Self {
+			color: AttachmentOps::LOAD,
+			depth: AttachmentOps::LOAD,
+			..Default::default()
+		}
+	}
+}
 
 pub struct RenderPass {
 	pub render_pass: vk::RenderPass,
+	/// Sample count of the color and depth attachments of the first subpass.
+	pub samples: vk::SampleCountFlags,
+	/// Pipelines must rasterize with the sample count of the subpass they are used in.
+	pub subpass_samples: Vec<vk::SampleCountFlags>,
+	/// Pipelines must blend exactly the color attachments of the subpass they are used in.
+	pub subpass_color_counts: Vec<u32>,
+	/// Values used by [`RenderPass::open`], in attachment order.
+	pub clear_values: Vec<vk::ClearValue>,
 }
 
 impl RenderPass {
+	/// `final_layout` is `PRESENT_SRC_KHR` for swapchain images and
+	/// `TRANSFER_SRC_OPTIMAL` for offscreen targets that are read back.
+	/// With more than one sample the color attachment is multisampled and resolved into
+	/// a third attachment that receives `final_layout`; see [`RenderPass::attachment_views`].
+	/// Passes that only differ in `config` are compatible and can share framebuffers and pipelines.
 	pub fn new(
 		device: &Device,
-		swapchain: &Swapchain,
-	) -> Self { unsafe {
-		let attachments = [
-			vk::AttachmentDescription::builder()
-				.format(swapchain.surface_format.format)
-				.samples(vk::SampleCountFlags::TYPE_1)
-				.load_op(vk::AttachmentLoadOp::CLEAR)
-				.store_op(vk::AttachmentStoreOp::STORE)
-				.final_layout(vk::ImageLayout::PRESENT_SRC_KHR)
-				.build(),
-			vk::AttachmentDescription::builder()
-				.format(vk::Format::D16_UNORM)
-				.samples(vk::SampleCountFlags::TYPE_1)
-				.load_op(vk::AttachmentLoadOp::CLEAR)
-				.initial_layout(vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL)
-				.final_layout(vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL)
-				.build(),
-		];
-		let color_attachment_references = [
-			vk::AttachmentReference::builder()
-				.attachment(0)
-				.layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL)
-				.build(),
-		];
-		let depth_attachment_reference = vk::AttachmentReference::builder()
-			.attachment(1)
-			.layout(vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL)
-			.build();
-		let dependencies = [
-			vk::SubpassDependency::builder()
-				.src_subpass(vk::SUBPASS_EXTERNAL)
-				.src_stage_mask(vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT)
-				.dst_access_mask(vk::AccessFlags::COLOR_ATTACHMENT_READ)
-				.dst_stage_mask(vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT)
-				.build(),
-		];
-		let subpass = vk::SubpassDescription::builder()
-			.color_attachments(color_attachment_references.as_slice())
-			.depth_stencil_attachment(&depth_attachment_reference)
-			.pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
-			.build();
-		let renderpass_info = vk::RenderPassCreateInfo::builder()
-			.attachments(attachments.as_slice())
-			.subpasses(std::slice::from_ref(&subpass))
-			.dependencies(dependencies.as_slice())
-			.build();
-		let renderpass = device.device.create_render_pass(
-			&renderpass_info,
-			None,
-		).unwrap();
-		Self {
-			render_pass: renderpass,
+		color_format: vk::Format,
+		final_layout: vk::ImageLayout,
+		samples: vk::SampleCountFlags,
+		config: &RenderPassConfig,
+	) -> Result<Self> {
+		RenderPass::from_desc(
+			device,
+			&RenderPassDesc::presentation(
+				device,
+				color_format,
+				final_layout,
+				samples,
+				config,
+			),
+		)
+	}
+
+	pub fn from_desc(
+		device: &Device,
+		desc: &RenderPassDesc,
+	) -> Result<Self> {
+		Ok(Self {
+			render_pass: desc.create(device)?,
+			samples: desc.samples(),
+			subpass_samples: desc.subpass_samples(),
+			subpass_color_counts: desc.subpass_color_counts(),
+			clear_values: desc.clear_values(),
+		})
+	}
+
+	/// Fails with `InvalidPipeline` when the pass has no such subpass.
+	pub fn subpass_sample_count(
+		&self,
+		subpass: u32,
+	) -> Result<vk::SampleCountFlags> {
+		self.subpass_samples.get(subpass as usize).copied().ok_or_else(
+			|| VpbError::InvalidPipeline(format!("render pass has no subpass {}", subpass))
+		)
+	}
+
+	/// Fails with `InvalidPipeline` when the pass has no such subpass.
+	pub fn subpass_color_attachment_count(
+		&self,
+		subpass: u32,
+	) -> Result<u32> {
+		self.subpass_color_counts.get(subpass as usize).copied().ok_or_else(
+			|| VpbError::InvalidPipeline(format!("render pass has no subpass {}", subpass))
+		)
+	}
+
+	/// Framebuffer views in attachment order. `color` is the single sampled image the pass ends in;
+	/// when multisampling it becomes the resolve attachment behind `multisampled_color`.
+	pub fn attachment_views(
+		color: vk::ImageView,
+		depth: vk::ImageView,
+		multisampled_color: Option<vk::ImageView>,
+	) -> Vec<vk::ImageView> {
+		match multisampled_color {
+			Some(multisampled_color) => vec![multisampled_color, depth, color],
+			None => vec![color, depth],
 		}
-	}}
+	}
 
 	pub fn open(
 		&self,
@@ -75,21 +155,7 @@ impl RenderPass {
 			.render_pass(self.render_pass)
 			.framebuffer(*framebuffer)
 			.render_area((*extent).into())
-			.clear_values(
-				&[
-					vk::ClearValue {
-						color: vk::ClearColorValue {
-							float32: [0.0, 0.0, 0.0, 1.0],
-						}
-					},
-					vk::ClearValue {
-						depth_stencil: vk::ClearDepthStencilValue {
-							depth: 1.0,
-							stencil: 0,
-						}
-					},
-				]
-			)
+			.clear_values(self.clear_values.as_slice())
 			.build();
 		device.device.cmd_begin_render_pass(
 			*command_buffer,
@@ -107,4 +173,11 @@ impl RenderPass {
 			command_buffer.command_buffer,
 		);
 	}}
+
+	pub fn destroy(
+		&self,
+		device: &Device,
+	) { unsafe {
+		device.device.destroy_render_pass(self.render_pass, None);
+	}}
 }
\ No newline at end of file
@@ -0,0 +1,637 @@
+use ash::vk;
+
+use crate::{Device, AttachmentOps, RenderPassConfig, Result, VpbError, format_has_stencil, format_is_depth_stencil};
+
+/// Attachment of a [`RenderPassDesc`]; framebuffers list their views in the same order.
+#[derive(Clone, Copy)]
+pub struct AttachmentDesc {
+	pub format: vk::Format,
+	pub samples: vk::SampleCountFlags,
+	pub ops: AttachmentOps,
+	/// Ignored unless `format` has a stencil aspect.
+	pub stencil_ops: AttachmentOps,
+	/// `None` is `UNDEFINED`, or `final_layout` when the attachment is loaded.
+	pub initial_layout: Option<vk::ImageLayout>,
+	pub final_layout: vk::ImageLayout,
+	/// Used when the attachment is cleared.
+	pub clear_value: vk::ClearValue,
+}
+
+impl AttachmentDesc {
+	/// Single sampled color attachment cleared to opaque black.
+	pub fn color(
+		format: vk::Format,
+		final_layout: vk::ImageLayout,
+	) -> Self {
+		Self {
+			format,
+			samples: vk::SampleCountFlags::TYPE_1,
+			ops: AttachmentOps::CLEAR,
+			stencil_ops: AttachmentOps::DONT_CARE,
+			initial_layout: None,
+			final_layout,
+			clear_value: vk::ClearValue {
+				color: vk::ClearColorValue {
+					float32: [0.0, 0.0, 0.0, 1.0],
+				}
+			},
+		}
+	}
+
+	/// Single sampled depth attachment cleared to 1.0, stencil included when the format has one.
+	pub fn depth(
+		format: vk::Format,
+		final_layout: vk::ImageLayout,
+	) -> Self {
+		Self {
+			format,
+			samples: vk::SampleCountFlags::TYPE_1,
+			ops: AttachmentOps::CLEAR,
+			stencil_ops: match format_has_stencil(format) {
+				true => AttachmentOps::CLEAR,
+				false => AttachmentOps::DONT_CARE,
+			},
+			initial_layout: None,
+			final_layout,
+			clear_value: vk::ClearValue {
+				depth_stencil: vk::ClearDepthStencilValue {
+					depth: 1.0,
+					stencil: 0,
+				}
+			},
+		}
+	}
+
+	pub fn samples(
+		mut self,
+		samples: vk::SampleCountFlags,
+	) -> Self {
+		self.samples = samples;
+		self
+	}
+
+	/// Also applied to stencil when the format has one.
+	pub fn ops(
+		mut self,
+		ops: AttachmentOps,
+	) -> Self {
+		self.ops = ops;
+		if format_has_stencil(self.format) {
+			self.stencil_ops = ops;
+		}
+		self
+	}
+
+	pub fn clear_value(
+		mut self,
+		clear_value: vk::ClearValue,
+	) -> Self {
+		self.clear_value = clear_value;
+		self
+	}
+
+	pub fn is_depth_stencil(
+		&self,
+	) -> bool {
+		format_is_depth_stencil(self.format)
+	}
+
+	fn loads(
+		&self,
+	) -> bool {
+		self.ops.load_op == vk::AttachmentLoadOp::LOAD ||
+		(format_has_stencil(self.format) && self.stencil_ops.load_op == vk::AttachmentLoadOp::LOAD)
+	}
+
+	fn description(
+		&self,
+	) -> vk::AttachmentDescription {
+		let stencil_ops = match format_has_stencil(self.format) {
+			true => self.stencil_ops,
+			false => AttachmentOps::DONT_CARE,
+		};
+		let initial_layout = self.initial_layout.unwrap_or(match self.loads() {
+			true => self.final_layout,
+			false => vk::ImageLayout::UNDEFINED,
+		});
+		vk::AttachmentDescription::builder()
+			.format(self.format)
+			.samples(self.samples)
+			.load_op(self.ops.load_op)
+			.store_op(self.ops.store_op)
+			.stencil_load_op(stencil_ops.load_op)
+			.stencil_store_op(stencil_ops.store_op)
+			.initial_layout(initial_layout)
+			.final_layout(self.final_layout)
+			.build()
+	}
+}
+
+/// Attachment indices used by one subpass.
+#[derive(Clone, Debug, Default)]
+pub struct SubpassDesc {
+	pub color: Vec<u32>,
+	/// Empty, or one single sampled target per `color` entry.
+	pub resolve: Vec<u32>,
+	/// Read in the fragment shader with `subpassLoad`; written by an earlier subpass.
+	pub input: Vec<u32>,
+	pub depth: Option<u32>,
+}
+
+impl SubpassDesc {
+	/// Attachments in use with the stages and accesses they are used by.
+	fn usage(
+		&self,
+	) -> Vec<(u32, vk::PipelineStageFlags, vk::AccessFlags)> {
+		let mut usage: Vec<(u32, vk::PipelineStageFlags, vk::AccessFlags)> = Vec::new();
+		let mut add = |attachment: u32, stage: vk::PipelineStageFlags, access: vk::AccessFlags| {
+			match usage.iter_mut().find(|(index, _, _)| *index == attachment) {
+				Some((_, stages, accesses)) => {
+					*stages |= stage;
+					*accesses |= access;
+				},
+				None => usage.push((attachment, stage, access)),
+			}
+		};
+		for attachment in self.color.iter().chain(self.resolve.iter()) {
+			add(
+				*attachment,
+				vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+				vk::AccessFlags::COLOR_ATTACHMENT_READ | vk::AccessFlags::COLOR_ATTACHMENT_WRITE,
+			);
+		}
+		if let Some(attachment) = self.depth {
+			add(
+				attachment,
+				vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS | vk::PipelineStageFlags::LATE_FRAGMENT_TESTS,
+				vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_READ | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE,
+			);
+		}
+		for attachment in self.input.iter() {
+			add(
+				*attachment,
+				vk::PipelineStageFlags::FRAGMENT_SHADER,
+				vk::AccessFlags::INPUT_ATTACHMENT_READ,
+			);
+		}
+		usage
+	}
+}
+
+/// Render pass with any number of attachments and subpasses.
+/// Subpass dependencies are generated from how consecutive subpasses use each attachment.
+#[derive(Clone, Default)]
+pub struct RenderPassDesc {
+	pub attachments: Vec<AttachmentDesc>,
+	/// Executed in order.
+	pub subpasses: Vec<SubpassDesc>,
+}
+
+impl RenderPassDesc {
+	/// Color and depth, plus a resolve target when multisampled; the pass `RenderPass::new` creates.
+	/// Attachments are ordered as [`crate::RenderPass::attachment_views`] returns them.
+	pub fn presentation(
+		device: &Device,
+		color_format: vk::Format,
+		final_layout: vk::ImageLayout,
+		samples: vk::SampleCountFlags,
+		config: &RenderPassConfig,
+	) -> Self {
+		let multisampled = samples != vk::SampleCountFlags::TYPE_1;
+		// The transient multisampled image is only read by the resolve; the resolve target is kept.
+		let color_ops = match multisampled {
+			true => AttachmentOps {
+				load_op: config.color.load_op,
+				store_op: vk::AttachmentStoreOp::DONT_CARE,
+			},
+			false => config.color,
+		};
+		let color = AttachmentDesc::color(
+			color_format,
+			match multisampled {
+				true => vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+				false => final_layout,
+			},
+		).samples(samples).ops(color_ops).clear_value(vk::ClearValue {
+			color: vk::ClearColorValue {
+				float32: config.clear_color,
+			}
+		});
+		let depth = AttachmentDesc::depth(
+			device.depth_format,
+			vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL,
+		).samples(samples).ops(config.depth).clear_value(vk::ClearValue {
+			depth_stencil: vk::ClearDepthStencilValue {
+				depth: config.clear_depth,
+				stencil: config.clear_stencil,
+			}
+		});
+		let mut desc = Self::default()
+			.attachment(color)
+			.attachment(depth);
+		let mut subpass = SubpassDesc {
+			color: vec![0],
+			depth: Some(1),
+			..Default::default()
+		};
+		if multisampled {
+			desc = desc.attachment(
+				AttachmentDesc::color(
+					color_format,
+					final_layout,
+				).ops(AttachmentOps {
+					load_op: vk::AttachmentLoadOp::DONT_CARE,
+					store_op: vk::AttachmentStoreOp::STORE,
+				}),
+			);
+			subpass.resolve = vec![2];
+		}
+		desc.subpass(subpass)
+	}
+
+	/// Single sampled color and depth whose color is sampled by later passes.
+	pub fn offscreen(
+		device: &Device,
+		color_format: vk::Format,
+		config: &RenderPassConfig,
+	) -> Self {
+		Self::presentation(
+			device,
+			color_format,
+			vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+			vk::SampleCountFlags::TYPE_1,
+			config,
+		)
+	}
+
+	pub fn attachment(
+		mut self,
+		attachment: AttachmentDesc,
+	) -> Self {
+		self.attachments.push(attachment);
+		self
+	}
+
+	pub fn subpass(
+		mut self,
+		subpass: SubpassDesc,
+	) -> Self {
+		self.subpasses.push(subpass);
+		self
+	}
+
+	/// Sample count of the first subpass's color or depth attachments.
+	pub fn samples(
+		&self,
+	) -> vk::SampleCountFlags {
+		self.subpass_samples().first().copied().unwrap_or(vk::SampleCountFlags::TYPE_1)
+	}
+
+	/// Sample count of each subpass's color or depth attachments, in subpass order.
+	pub fn subpass_samples(
+		&self,
+	) -> Vec<vk::SampleCountFlags> {
+		self.subpasses.iter().map(
+			|subpass|
+			subpass.color.first().copied().or(subpass.depth).and_then(
+				|attachment|
+				self.attachments.get(attachment as usize)
+			).map_or(vk::SampleCountFlags::TYPE_1, |attachment| attachment.samples)
+		).collect()
+	}
+
+	/// Number of color attachments of each subpass, in subpass order.
+	pub fn subpass_color_counts(
+		&self,
+	) -> Vec<u32> {
+		self.subpasses.iter().map(|subpass| subpass.color.len() as u32).collect()
+	}
+
+	pub fn clear_values(
+		&self,
+	) -> Vec<vk::ClearValue> {
+		self.attachments.iter().map(|attachment| attachment.clear_value).collect()
+	}
+
+	pub fn create(
+		&self,
+		device: &Device,
+	) -> Result<vk::RenderPass> { unsafe {
+		self.validate()?;
+		let attachments: Vec<vk::AttachmentDescription> = self.attachments.iter().map(
+			|attachment|
+			attachment.description()
+		).collect();
+		let reference = |attachment: u32, layout: vk::ImageLayout| {
+			vk::AttachmentReference::builder()
+				.attachment(attachment)
+				.layout(layout)
+				.build()
+		};
+		let color_references: Vec<Vec<vk::AttachmentReference>> = self.subpasses.iter().map(
+			|subpass|
+			subpass.color.iter().map(|attachment| reference(*attachment, vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL)).collect()
+		).collect();
+		let resolve_references: Vec<Vec<vk::AttachmentReference>> = self.subpasses.iter().map(
+			|subpass|
+			subpass.resolve.iter().map(|attachment| reference(*attachment, vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL)).collect()
+		).collect();
+		let input_references: Vec<Vec<vk::AttachmentReference>> = self.subpasses.iter().map(
+			|subpass|
+			subpass.input.iter().map(|attachment| reference(
+				*attachment,
+				match self.attachments[*attachment as usize].is_depth_stencil() {
+					true => vk::ImageLayout::DEPTH_STENCIL_READ_ONLY_OPTIMAL,
+					false => vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+				},
+			)).collect()
+		).collect();
+		let depth_references: Vec<Option<vk::AttachmentReference>> = self.subpasses.iter().map(
+			|subpass|
+			subpass.depth.map(|attachment| reference(attachment, vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL))
+		).collect();
+		let preserve_attachments = self.preserve_attachments();
+		let subpasses: Vec<vk::SubpassDescription> = (0..self.subpasses.len()).map(|index| {
+			let mut subpass = vk::SubpassDescription::builder()
+				.pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
+				.color_attachments(color_references[index].as_slice())
+				.input_attachments(input_references[index].as_slice())
+				.preserve_attachments(preserve_attachments[index].as_slice());
+			if !resolve_references[index].is_empty() {
+				subpass = subpass.resolve_attachments(resolve_references[index].as_slice());
+			}
+			if let Some(depth_reference) = &depth_references[index] {
+				subpass = subpass.depth_stencil_attachment(depth_reference);
+			}
+			subpass.build()
+		}).collect();
+		let dependencies = self.dependencies();
+		let render_pass_info = vk::RenderPassCreateInfo::builder()
+			.attachments(attachments.as_slice())
+			.subpasses(subpasses.as_slice())
+			.dependencies(dependencies.as_slice())
+			.build();
+		Ok(device.device.create_render_pass(
+			&render_pass_info,
+			None,
+		)?)
+	}}
+
+	fn validate(
+		&self,
+	) -> Result<()> {
+		if self.subpasses.is_empty() {
+			return Err(VpbError::InvalidRenderPass("no subpasses".to_string()));
+		}
+		for (index, subpass) in self.subpasses.iter().enumerate() {
+			if !subpass.resolve.is_empty() && subpass.resolve.len() != subpass.color.len() {
+				return Err(VpbError::InvalidRenderPass(format!(
+					"subpass {} has {} resolve attachments for {} color attachments",
+					index, subpass.resolve.len(), subpass.color.len(),
+				)));
+			}
+			for (attachment, _, _) in subpass.usage() {
+				if attachment as usize >= self.attachments.len() {
+					return Err(VpbError::InvalidRenderPass(format!(
+						"subpass {} uses attachment {} of {}",
+						index, attachment, self.attachments.len(),
+					)));
+				}
+			}
+		}
+		Ok(())
+	}
+
+	/// Attachments a subpass does not use but whose contents an earlier subpass hands to a later one.
+	fn preserve_attachments(
+		&self,
+	) -> Vec<Vec<u32>> {
+		let usage: Vec<Vec<u32>> = self.subpasses.iter().map(
+			|subpass|
+			subpass.usage().into_iter().map(|(attachment, _, _)| attachment).collect()
+		).collect();
+		(0..usage.len()).map(|index| {
+			(0..self.attachments.len() as u32).filter(
+				|attachment|
+				!usage[index].contains(attachment) &&
+				usage[..index].iter().any(|used| used.contains(attachment)) &&
+				usage[index + 1..].iter().any(|used| used.contains(attachment))
+			).collect()
+		}).collect()
+	}
+
+	/// Dependencies from the previous user of each attachment, `SUBPASS_EXTERNAL` for the first,
+	/// and from the last user to `SUBPASS_EXTERNAL` when the final layout is read outside the pass.
+	fn dependencies(
+		&self,
+	) -> Vec<vk::SubpassDependency> {
+		let writes = vk::AccessFlags::COLOR_ATTACHMENT_WRITE | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE;
+		let mut dependencies: Vec<vk::SubpassDependency> = Vec::new();
+		let mut add = |
+			src_subpass: u32,
+			dst_subpass: u32,
+			src_stage: vk::PipelineStageFlags,
+			src_access: vk::AccessFlags,
+			dst_stage: vk::PipelineStageFlags,
+			dst_access: vk::AccessFlags,
+		| {
+			let position = match dependencies.iter().position(
+				|dependency|
+				dependency.src_subpass == src_subpass && dependency.dst_subpass == dst_subpass
+			) {
+				Some(position) => position,
+				None => {
+					dependencies.push(vk::SubpassDependency::builder()
+						.src_subpass(src_subpass)
+						.dst_subpass(dst_subpass)
+						.dependency_flags(match src_subpass == vk::SUBPASS_EXTERNAL || dst_subpass == vk::SUBPASS_EXTERNAL {
+							true => vk::DependencyFlags::empty(),
+							false => vk::DependencyFlags::BY_REGION,
+						})
+						.build());
+					dependencies.len() - 1
+				},
+			};
+			let dependency = &mut dependencies[position];
+			dependency.src_stage_mask |= src_stage;
+			dependency.src_access_mask |= src_access;
+			dependency.dst_stage_mask |= dst_stage;
+			dependency.dst_access_mask |= dst_access;
+		};
+		let mut last_use: Vec<Option<(u32, vk::PipelineStageFlags, vk::AccessFlags)>> = vec![None; self.attachments.len()];
+		for (index, subpass) in self.subpasses.iter().enumerate() {
+			let index = index as u32;
+			for (attachment, stage, access) in subpass.usage() {
+				match last_use[attachment as usize] {
+					Some((src_subpass, src_stage, src_access)) => add(
+						src_subpass,
+						index,
+						src_stage,
+						src_access & writes,
+						stage,
+						access,
+					),
+					// Waits for earlier frames still using the image, and for the acquire semaphore.
+					None => add(
+						vk::SUBPASS_EXTERNAL,
+						index,
+						stage,
+						access & writes,
+						stage,
+						access,
+					),
+				}
+				last_use[attachment as usize] = Some((index, stage, access));
+			}
+		}
+		for (attachment, last_use) in self.attachments.iter().zip(last_use) {
+			let Some((src_subpass, src_stage, src_access)) = last_use else {
+				continue;
+			};
+			let (dst_stage, dst_access) = match attachment.final_layout {
+				vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL |
+				vk::ImageLayout::DEPTH_STENCIL_READ_ONLY_OPTIMAL => (
+					vk::PipelineStageFlags::FRAGMENT_SHADER,
+					vk::AccessFlags::SHADER_READ,
+				),
+				vk::ImageLayout::TRANSFER_SRC_OPTIMAL => (
+					vk::PipelineStageFlags::TRANSFER,
+					vk::AccessFlags::TRANSFER_READ,
+				),
+				_ => { continue; },
+			};
+			add(
+				src_subpass,
+				vk::SUBPASS_EXTERNAL,
+				src_stage,
+				src_access & writes,
+				dst_stage,
+				dst_access,
+			);
+		}
+		dependencies
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn color() -> AttachmentDesc {
+		AttachmentDesc::color(
+			vk::Format::R8G8B8A8_UNORM,
+			vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+		)
+	}
+
+	fn dependency(
+		dependencies: &[vk::SubpassDependency],
+		src_subpass: u32,
+		dst_subpass: u32,
+	) -> Option<vk::SubpassDependency> {
+		dependencies.iter().find(
+			|dependency|
+			dependency.src_subpass == src_subpass && dependency.dst_subpass == dst_subpass
+		).copied()
+	}
+
+	#[test]
+	fn color_becomes_input() {
+		let desc = RenderPassDesc::default()
+			.attachment(color())
+			.attachment(color())
+			.subpass(SubpassDesc {
+				color: vec![0],
+				..Default::default()
+			})
+			.subpass(SubpassDesc {
+				color: vec![1],
+				input: vec![0],
+				..Default::default()
+			});
+		let dependencies = desc.dependencies();
+		assert_eq!(dependencies.len(), 3);
+		assert!(dependency(&dependencies, vk::SUBPASS_EXTERNAL, 0).is_some());
+		assert!(dependency(&dependencies, vk::SUBPASS_EXTERNAL, 1).is_some());
+		let dependency = dependency(&dependencies, 0, 1).unwrap();
+		assert_eq!(dependency.src_stage_mask, vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT);
+		assert_eq!(dependency.src_access_mask, vk::AccessFlags::COLOR_ATTACHMENT_WRITE);
+		assert_eq!(dependency.dst_stage_mask, vk::PipelineStageFlags::FRAGMENT_SHADER);
+		assert_eq!(dependency.dst_access_mask, vk::AccessFlags::INPUT_ATTACHMENT_READ);
+		assert_eq!(dependency.dependency_flags, vk::DependencyFlags::BY_REGION);
+		assert_eq!(desc.preserve_attachments(), vec![Vec::<u32>::new(); 2]);
+	}
+
+	#[test]
+	fn depth_reused() {
+		let tests = vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS | vk::PipelineStageFlags::LATE_FRAGMENT_TESTS;
+		let desc = RenderPassDesc::default()
+			.attachment(color())
+			.attachment(color())
+			.attachment(AttachmentDesc::depth(
+				vk::Format::D32_SFLOAT,
+				vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL,
+			))
+			.subpass(SubpassDesc {
+				color: vec![0],
+				depth: Some(2),
+				..Default::default()
+			})
+			.subpass(SubpassDesc {
+				color: vec![1],
+				depth: Some(2),
+				..Default::default()
+			});
+		let dependencies = desc.dependencies();
+		let external = dependency(&dependencies, vk::SUBPASS_EXTERNAL, 0).unwrap();
+		assert_eq!(external.dst_stage_mask, vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT | tests);
+		assert_eq!(dependencies.iter().filter(|dependency| dependency.src_subpass == 0).count(), 1);
+		let dependency = dependency(&dependencies, 0, 1).unwrap();
+		assert_eq!(dependency.src_stage_mask, tests);
+		assert_eq!(dependency.src_access_mask, vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE);
+		assert_eq!(dependency.dst_stage_mask, tests);
+		assert_eq!(
+			dependency.dst_access_mask,
+			vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_READ | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE,
+		);
+		assert_eq!(desc.preserve_attachments(), vec![Vec::<u32>::new(); 2]);
+	}
+
+	#[test]
+	fn skipped_by_middle_subpass() {
+		let desc = RenderPassDesc::default()
+			.attachment(color())
+			.attachment(color())
+			.attachment(AttachmentDesc::color(
+				vk::Format::R8G8B8A8_UNORM,
+				vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+			))
+			.subpass(SubpassDesc {
+				color: vec![0],
+				..Default::default()
+			})
+			.subpass(SubpassDesc {
+				color: vec![1],
+				..Default::default()
+			})
+			.subpass(SubpassDesc {
+				color: vec![2],
+				input: vec![0],
+				..Default::default()
+			});
+		assert_eq!(desc.preserve_attachments(), vec![vec![], vec![0], vec![]]);
+		let dependencies = desc.dependencies();
+		assert!(dependency(&dependencies, 0, 1).is_none());
+		assert!(dependency(&dependencies, 1, 2).is_none());
+		let skipping = dependency(&dependencies, 0, 2).unwrap();
+		assert_eq!(skipping.src_access_mask, vk::AccessFlags::COLOR_ATTACHMENT_WRITE);
+		assert_eq!(skipping.dst_stage_mask, vk::PipelineStageFlags::FRAGMENT_SHADER);
+		assert_eq!(skipping.dst_access_mask, vk::AccessFlags::INPUT_ATTACHMENT_READ);
+		// Attachment 1 is only written and stays in its attachment layout, so nothing outside waits.
+		let external = dependency(&dependencies, 2, vk::SUBPASS_EXTERNAL).unwrap();
+		assert_eq!(external.src_access_mask, vk::AccessFlags::COLOR_ATTACHMENT_WRITE);
+		assert_eq!(external.dst_stage_mask, vk::PipelineStageFlags::FRAGMENT_SHADER);
+		assert_eq!(external.dst_access_mask, vk::AccessFlags::SHADER_READ);
+		assert!(dependency(&dependencies, 1, vk::SUBPASS_EXTERNAL).is_none());
+	}
+}
\ No newline at end of file
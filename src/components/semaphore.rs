@@ -0,0 +1,27 @@
+use ash::vk;
+
+use crate::{Device, Result};
+
+pub struct Semaphore {
+	pub semaphore: vk::Semaphore,
+}
+
+impl Semaphore {
+	pub fn new(
+		device: &Device,
+	) -> Result<Self> { unsafe {
+		let semaphore_info = vk::SemaphoreCreateInfo::builder()
+			.build();
+		let semaphore = device.device.create_semaphore(&semaphore_info, None)?;
+		Ok(Self {
+			semaphore,
+		})
+	}}
+
+	pub fn destroy(
+		&self,
+		device: &Device,
+	) { unsafe {
+		device.device.destroy_semaphore(self.semaphore, None);
+	}}
+}
\ No newline at end of file
@@ -0,0 +1,256 @@
+use std::fs;
+
+use crate::{Result, VpbError};
+
+/// "vpbs" little endian.
+const CACHE_MAGIC: u32 = 0x7362_7076;
+/// Bump when the file layout or anything compiled into every shader changes.
+pub const SHADER_CACHE_VERSION: u32 = 1;
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// FNV-1a; unlike `DefaultHasher` it is stable across runs and toolchains.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ShaderHash(pub u64);
+
+impl Default for ShaderHash {
+	fn default() -> Self {
+		Self(FNV_OFFSET)
+	}
+}
+
+impl ShaderHash {
+	pub fn of(
+		bytes: &[u8],
+	) -> Self {
+		Self::default().write(bytes)
+	}
+
+	/// Length prefixed so consecutive fields cannot run into each other.
+	pub fn write(
+		self,
+		bytes: &[u8],
+	) -> Self {
+		let mut hash = self.0;
+		for byte in (bytes.len() as u64).to_le_bytes().iter().chain(bytes.iter()) {
+			hash ^= *byte as u64;
+			hash = hash.wrapping_mul(FNV_PRIME);
+		}
+		Self(hash)
+	}
+}
+
+/// Compiled SPIR-V stored next to what it was compiled from.
+pub struct CachedShader {
+	/// Hash of the compile options, defines and source; the file name leaves out the source.
+	pub key: ShaderHash,
+	/// Files pulled in while compiling and the hash of their contents at the time.
+	pub dependencies: Vec<(String, ShaderHash)>,
+	pub spirv: Vec<u32>,
+}
+
+impl CachedShader {
+	/// `None` when the file is missing, from another cache version or malformed.
+	pub fn read(
+		path: &str,
+	) -> Option<Self> {
+		let bytes = fs::read(path).ok()?;
+		let mut reader = CacheReader {
+			bytes: bytes.as_slice(),
+		};
+		if reader.u32()? != CACHE_MAGIC || reader.u32()? != SHADER_CACHE_VERSION {
+			return None;
+		}
+		let key = ShaderHash(reader.u64()?);
+		let dependency_count = reader.u32()? as usize;
+		// Counts come from the file; a corrupt one must not reserve more than it could hold.
+		if !reader.fits(dependency_count, 12) {
+			return None;
+		}
+		let mut dependencies = Vec::with_capacity(dependency_count);
+		for _ in 0..dependency_count {
+			let length = reader.u32()? as usize;
+			let path = String::from_utf8(reader.take(length)?.to_vec()).ok()?;
+			dependencies.push((path, ShaderHash(reader.u64()?)));
+		}
+		let word_count = reader.u32()? as usize;
+		if !reader.fits(word_count, 4) {
+			return None;
+		}
+		let mut spirv = Vec::with_capacity(word_count);
+		for _ in 0..word_count {
+			spirv.push(reader.u32()?);
+		}
+		Some(Self {
+			key,
+			dependencies,
+			spirv,
+		})
+	}
+
+	/// True when compiled from `key` and no dependency changed on disk since.
+	pub fn is_valid(
+		&self,
+		key: ShaderHash,
+	) -> bool {
+		self.key == key && self.dependencies.iter().all(
+			|(path, hash)|
+			fs::read(path).is_ok_and(|bytes| ShaderHash::of(&bytes) == *hash)
+		)
+	}
+
+	pub fn write(
+		&self,
+		path: &str,
+	) -> Result<()> {
+		let mut bytes = Vec::with_capacity(24 + self.spirv.len() * 4);
+		bytes.extend_from_slice(&CACHE_MAGIC.to_le_bytes());
+		bytes.extend_from_slice(&SHADER_CACHE_VERSION.to_le_bytes());
+		bytes.extend_from_slice(&self.key.0.to_le_bytes());
+		bytes.extend_from_slice(&(self.dependencies.len() as u32).to_le_bytes());
+		for (dependency, hash) in self.dependencies.iter() {
+			bytes.extend_from_slice(&(dependency.len() as u32).to_le_bytes());
+			bytes.extend_from_slice(dependency.as_bytes());
+			bytes.extend_from_slice(&hash.0.to_le_bytes());
+		}
+		bytes.extend_from_slice(&(self.spirv.len() as u32).to_le_bytes());
+		for word in self.spirv.iter() {
+			bytes.extend_from_slice(&word.to_le_bytes());
+		}
+		fs::write(path, bytes).map_err(
+			|error| VpbError::Io(path.to_string(), error)
+		)
+	}
+}
+
+struct CacheReader<'a> {
+	bytes: &'a [u8],
+}
+
+impl<'a> CacheReader<'a> {
+	/// Whether `count` elements of at least `size` bytes can still follow.
+	fn fits(
+		&self,
+		count: usize,
+		size: usize,
+	) -> bool {
+		count.checked_mul(size).is_some_and(|length| length <= self.bytes.len())
+	}
+
+	fn take(
+		&mut self,
+		length: usize,
+	) -> Option<&'a [u8]> {
+		if self.bytes.len() < length {
+			return None;
+		}
+		let (taken, rest) = self.bytes.split_at(length);
+		self.bytes = rest;
+		Some(taken)
+	}
+
+	fn u32(
+		&mut self,
+	) -> Option<u32> {
+		Some(u32::from_le_bytes(self.take(4)?.try_into().ok()?))
+	}
+
+	fn u64(
+		&mut self,
+	) -> Option<u64> {
+		Some(u64::from_le_bytes(self.take(8)?.try_into().ok()?))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{fs, path::PathBuf};
+
+	use super::*;
+
+	fn temp_path(
+		name: &str,
+	) -> String {
+		let path: PathBuf = std::env::temp_dir().join(format!("vpb_{}_{}.spv", name, std::process::id()));
+		path.to_string_lossy().to_string()
+	}
+
+	fn shader(
+		dependency: &str,
+	) -> CachedShader {
+		CachedShader {
+			key: ShaderHash::of(b"void main() {}"),
+			dependencies: vec![(dependency.to_string(), ShaderHash::of(b"#define A"))],
+			spirv: vec![0x0723_0203, 1, 2, 3],
+		}
+	}
+
+	#[test]
+	fn hash_is_length_prefixed() {
+		assert_ne!(
+			ShaderHash::of(b"ab").write(b"c"),
+			ShaderHash::of(b"a").write(b"bc"),
+		);
+		assert_ne!(ShaderHash::of(b""), ShaderHash::default());
+		assert_eq!(ShaderHash::of(b"abc"), ShaderHash::of(b"abc"));
+	}
+
+	#[test]
+	fn round_trip() {
+		let dependency = temp_path("round_trip_include");
+		fs::write(&dependency, b"#define A").unwrap();
+		let path = temp_path("round_trip");
+		let written = shader(&dependency);
+		written.write(&path).unwrap();
+		let read = CachedShader::read(&path).unwrap();
+		assert_eq!(read.key, written.key);
+		assert_eq!(read.dependencies, written.dependencies);
+		assert_eq!(read.spirv, written.spirv);
+		assert!(read.is_valid(written.key));
+		assert!(!read.is_valid(ShaderHash::of(b"other")));
+		fs::write(&dependency, b"#define B").unwrap();
+		assert!(!read.is_valid(written.key));
+		fs::remove_file(&path).unwrap();
+		fs::remove_file(&dependency).unwrap();
+	}
+
+	#[test]
+	fn rejects_truncated() {
+		let path = temp_path("truncated");
+		shader("include.glsl").write(&path).unwrap();
+		let bytes = fs::read(&path).unwrap();
+		for length in 0..bytes.len() {
+			fs::write(&path, &bytes[..length]).unwrap();
+			assert!(CachedShader::read(&path).is_none(), "accepted {} of {} bytes", length, bytes.len());
+		}
+		fs::remove_file(&path).unwrap();
+	}
+
+	#[test]
+	fn rejects_other_version() {
+		let path = temp_path("version");
+		shader("include.glsl").write(&path).unwrap();
+		let mut bytes = fs::read(&path).unwrap();
+		bytes[4..8].copy_from_slice(&(SHADER_CACHE_VERSION + 1).to_le_bytes());
+		fs::write(&path, &bytes).unwrap();
+		assert!(CachedShader::read(&path).is_none());
+		fs::remove_file(&path).unwrap();
+	}
+
+	#[test]
+	fn rejects_oversized_counts() {
+		let path = temp_path("counts");
+		CachedShader {
+			key: ShaderHash::default(),
+			dependencies: Vec::new(),
+			spirv: Vec::new(),
+		}.write(&path).unwrap();
+		let mut bytes = fs::read(&path).unwrap();
+		let length = bytes.len();
+		bytes[length - 4..].copy_from_slice(&u32::MAX.to_le_bytes());
+		fs::write(&path, &bytes).unwrap();
+		assert!(CachedShader::read(&path).is_none());
+		fs::remove_file(&path).unwrap();
+	}
+}
\ No newline at end of file
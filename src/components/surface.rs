@@ -1,9 +1,9 @@
 use std::{marker::PhantomData, ptr};
 
-use ash::vk;
+use ash::{vk, extensions::khr};
 use raw_window_handle::{HasRawDisplayHandle, HasRawWindowHandle};
 
-use crate::{Window, Instance};
+use crate::{Window, Instance, Result};
 
 pub struct Surface {
 	pub surface: vk::SurfaceKHR,
@@ -13,7 +13,7 @@ impl Surface {
 	pub fn new(
 		instance: &Instance,
 		window: &Window,
-	) -> Self { unsafe {
+	) -> Result<Self> { unsafe {
 		// let surface = ash_window::create_surface(
 		// 	&instance.entry,
 		// 	&instance.instance,
@@ -23,13 +23,24 @@ impl Surface {
 		// ).unwrap();
 
 		let mut surface: std::mem::MaybeUninit<vk::SurfaceKHR> = std::mem::MaybeUninit::uninit();
-		if window.window.create_window_surface(instance.instance.handle(), ptr::null(), surface.as_mut_ptr())
-			!= vk::Result::SUCCESS
-		{
-			panic!("Failed to create GLFW window surface.");
-		}
-		Self {
+		window.window.create_window_surface(
+			instance.instance.handle(),
+			ptr::null(),
+			surface.as_mut_ptr(),
+		).result()?;
+		Ok(Self {
 			surface: surface.assume_init(),
-		}
+		})
+	}}
+
+	pub fn destroy(
+		&self,
+		instance: &Instance,
+	) { unsafe {
+		let surface_loader = khr::Surface::new(
+			&instance.entry,
+			&instance.instance,
+		);
+		surface_loader.destroy_surface(self.surface, None);
 	}}
 }
\ No newline at end of file
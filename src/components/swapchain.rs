@@ -1,13 +1,104 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
 use ash::{vk, extensions::khr};
 use glfw::Context;
 
-use crate::{Device, Window, Instance, Surface};
+use crate::{Device, Window, Instance, Surface, Result, VpbError};
+
+/// Color space extension required by the HDR formats.
+pub const SWAPCHAIN_COLORSPACE_EXTENSION: &str = "VK_EXT_swapchain_colorspace";
+
+pub const SURFACE_FORMAT_SRGB: vk::SurfaceFormatKHR = vk::SurfaceFormatKHR {
+	format: vk::Format::B8G8R8A8_SRGB,
+	color_space: vk::ColorSpaceKHR::SRGB_NONLINEAR,
+};
+
+pub const SURFACE_FORMAT_HDR10: vk::SurfaceFormatKHR = vk::SurfaceFormatKHR {
+	format: vk::Format::A2B10G10R10_UNORM_PACK32,
+	color_space: vk::ColorSpaceKHR::HDR10_ST2084_EXT,
+};
+
+/// Linear extended sRGB (scRGB).
+pub const SURFACE_FORMAT_SCRGB: vk::SurfaceFormatKHR = vk::SurfaceFormatKHR {
+	format: vk::Format::R16G16B16A16_SFLOAT,
+	color_space: vk::ColorSpaceKHR::EXTENDED_SRGB_LINEAR_EXT,
+};
+
+#[derive(Clone, Debug)]
+pub struct SwapchainConfig {
+	/// First mode the surface supports is used; FIFO is the fallback as it is always supported.
+	pub present_modes: Vec<vk::PresentModeKHR>,
+	/// First format the surface supports is used, otherwise whatever the surface lists first.
+	/// Only read on creation; the format stays fixed across recreation so render passes remain compatible.
+	pub surface_formats: Vec<vk::SurfaceFormatKHR>,
+}
+
+impl Default for SwapchainConfig {
+	fn default() -> Self {
+		Self {
+			present_modes: vec![
+				vk::PresentModeKHR::MAILBOX,
+				vk::PresentModeKHR::FIFO,
+			],
+			surface_formats: vec![
+				SURFACE_FORMAT_SRGB,
+			],
+		}
+	}
+}
+
+impl SwapchainConfig {
+	/// FIFO when enabled, otherwise the lowest latency mode available.
+	pub fn vsync(
+		enabled: bool,
+	) -> Self {
+		let present_modes = match enabled {
+			true => vec![
+				vk::PresentModeKHR::FIFO,
+			],
+			false => vec![
+				vk::PresentModeKHR::IMMEDIATE,
+				vk::PresentModeKHR::MAILBOX,
+				vk::PresentModeKHR::FIFO_RELAXED,
+			],
+		};
+		Self {
+			present_modes,
+			..Default::default()
+		}
+	}
+
+	/// Prefers HDR10, then scRGB, over the current formats.
+	pub fn prefer_hdr(
+		mut self,
+	) -> Self {
+		self.surface_formats.splice(0..0, [
+			SURFACE_FORMAT_HDR10,
+			SURFACE_FORMAT_SCRGB,
+		]);
+		self
+	}
+
+	/// True when a preferred format needs [`SWAPCHAIN_COLORSPACE_EXTENSION`].
+	pub fn needs_colorspace_extension(
+		&self,
+	) -> bool {
+		self.surface_formats.iter().any(
+			|surface_format|
+			surface_format.color_space != vk::ColorSpaceKHR::SRGB_NONLINEAR
+		)
+	}
+}
 
 pub struct Swapchain {
 	pub swapchain_loader: khr::Swapchain,
 	pub swapchain: vk::SwapchainKHR,
 	pub present_queue: vk::Queue,
 	pub surface_format: vk::SurfaceFormatKHR,
+	pub extent: vk::Extent2D,
+	pub present_mode: vk::PresentModeKHR,
+	/// Set once passed as `old_swapchain`; a retired swapchain cannot be passed again.
+	retired: AtomicBool,
 }
 
 impl Swapchain {
@@ -16,27 +107,85 @@ impl Swapchain {
 		window: &mut Window,
 		surface: &Surface,
 		device: &Device,
-	) -> Self { unsafe {
-		let present_queue = device.device.get_device_queue(
-			device.queue_family_index[0],
-			0
-		);
-		let surface_format =
-			device.surface_loader
-			.get_physical_device_surface_formats(
-				device.physical_device,
-				surface.surface,
-			).unwrap()[0];
+		config: &SwapchainConfig,
+	) -> Result<Self> {
+		let swapchain_loader = khr::Swapchain::new(&instance.instance, &device.device);
+		let swapchain = Swapchain::create(
+			swapchain_loader,
+			window,
+			surface,
+			device,
+			config,
+			None,
+			None,
+		)?.ok_or(VpbError::Window("window has no drawable area".to_string()))?;
+		window.extent = swapchain.extent;
+		Ok(swapchain)
+	}
+
+	/// Replacement for `self` sized to the window's current framebuffer; `None` while minimized.
+	/// `self` is retired but stays alive until [`Swapchain::destroy`] is called.
+	pub fn recreate(
+		&self,
+		window: &Window,
+		surface: &Surface,
+		device: &Device,
+		config: &SwapchainConfig,
+	) -> Result<Option<Self>> {
+		Swapchain::create(
+			self.swapchain_loader.clone(),
+			window,
+			surface,
+			device,
+			config,
+			Some(self.surface_format),
+			Some(self),
+		)
+	}
+
+	fn create(
+		swapchain_loader: khr::Swapchain,
+		window: &Window,
+		surface: &Surface,
+		device: &Device,
+		config: &SwapchainConfig,
+		surface_format: Option<vk::SurfaceFormatKHR>,
+		old_swapchain: Option<&Swapchain>,
+	) -> Result<Option<Self>> { unsafe {
+		let present_queue = device.queues.present.queue;
+		let surface_format = match surface_format {
+			Some(surface_format) => surface_format,
+			None => Swapchain::select_surface_format(
+				surface,
+				device,
+				config,
+			)?,
+		};
 		let surface_capabilities =
 			device.surface_loader
 			.get_physical_device_surface_capabilities(
 				device.physical_device,
 				surface.surface,
-			).unwrap();
-		window.extent = match surface_capabilities.current_extent.width {
-			u32::MAX => window.extent,
+			)?;
+		let extent = match surface_capabilities.current_extent.width {
+			u32::MAX => {
+				let (width, height) = window.window.get_framebuffer_size();
+				vk::Extent2D {
+					width: (width.max(0) as u32).clamp(
+						surface_capabilities.min_image_extent.width,
+						surface_capabilities.max_image_extent.width,
+					),
+					height: (height.max(0) as u32).clamp(
+						surface_capabilities.min_image_extent.height,
+						surface_capabilities.max_image_extent.height,
+					),
+				}
+			},
 			_ => surface_capabilities.current_extent,
 		};
+		if extent.width == 0 || extent.height == 0 {
+			return Ok(None);
+		}
 
 		let mut desired_image_count = surface_capabilities.min_image_count + 1;
 		if surface_capabilities.max_image_count > 0 &&
@@ -51,37 +200,128 @@ impl Swapchain {
 				surface_capabilities.current_transform
 			};
 		let present_modes = device.surface_loader
-			.get_physical_device_surface_present_modes(device.physical_device, surface.surface)
-			.unwrap();
-		let present_mode = present_modes
+			.get_physical_device_surface_present_modes(device.physical_device, surface.surface)?;
+		let present_mode = config.present_modes
 			.iter().cloned().find(
-				|&mode|
-				mode == vk::PresentModeKHR::MAILBOX
+				|mode|
+				present_modes.contains(mode)
 			).unwrap_or(vk::PresentModeKHR::FIFO);
-		let swapchain_loader = khr::Swapchain::new(&instance.instance, &device.device);
+		// Images are shared between the graphics and present families when they differ.
+		let queue_family_indices = [
+			device.queues.graphics.family_index,
+			device.queues.present.family_index,
+		];
+		let (image_sharing_mode, queue_family_indices) = match queue_family_indices[0] == queue_family_indices[1] {
+			true => (vk::SharingMode::EXCLUSIVE, &queue_family_indices[..0]),
+			false => (vk::SharingMode::CONCURRENT, &queue_family_indices[..]),
+		};
+		// Creation retires `old_swapchain` even when it fails; a retry after that starts from scratch.
+		let old_swapchain = match old_swapchain {
+			Some(old_swapchain) if !old_swapchain.retired.swap(true, Ordering::Relaxed) => old_swapchain.swapchain,
+			_ => vk::SwapchainKHR::null(),
+		};
 		let swapchain_info =
 			vk::SwapchainCreateInfoKHR::builder()
 			.surface(surface.surface)
 			.min_image_count(desired_image_count)
 			.image_color_space(surface_format.color_space)
 			.image_format(surface_format.format)
-			.image_extent(window.extent)
+			.image_extent(extent)
 			.image_usage(vk::ImageUsageFlags::COLOR_ATTACHMENT)
-			.image_sharing_mode(vk::SharingMode::EXCLUSIVE)
+			.image_sharing_mode(image_sharing_mode)
+			.queue_family_indices(&queue_family_indices)
 			.pre_transform(pre_transform)
 			.composite_alpha(vk::CompositeAlphaFlagsKHR::OPAQUE)
 			.present_mode(present_mode)
 			.clipped(true)
+			.old_swapchain(old_swapchain)
 			.image_array_layers(1).build();
 		let swapchain = swapchain_loader.create_swapchain(
 			&swapchain_info,
 			None,
-		).unwrap();	
-		Self {
+		)?;
+		Ok(Some(Self {
 			swapchain_loader,
 			swapchain,
 			present_queue,
 			surface_format,
+			extent,
+			present_mode,
+			retired: AtomicBool::new(false),
+		}))
+	}}
+
+	fn select_surface_format(
+		surface: &Surface,
+		device: &Device,
+		config: &SwapchainConfig,
+	) -> Result<vk::SurfaceFormatKHR> { unsafe {
+		let surface_formats =
+			device.surface_loader
+			.get_physical_device_surface_formats(
+				device.physical_device,
+				surface.surface,
+			)?;
+		// A single UNDEFINED entry means the surface has no preference.
+		if let [vk::SurfaceFormatKHR { format: vk::Format::UNDEFINED, .. }] = surface_formats.as_slice() {
+			return Ok(config.surface_formats.first().copied().unwrap_or(SURFACE_FORMAT_SRGB));
+		}
+		Ok(config.surface_formats
+			.iter().copied().find(
+				|preferred|
+				surface_formats.contains(preferred)
+			).or(surface_formats.first().copied())
+			.ok_or(VpbError::Vulkan(vk::Result::ERROR_FORMAT_NOT_SUPPORTED))?)
+	}}
+
+	/// Image index and whether the swapchain is suboptimal; `None` when it is out of date and must be recreated.
+	pub fn acquire_next_image(
+		&self,
+		semaphore: vk::Semaphore,
+		fence: vk::Fence,
+	) -> Result<Option<(u32, bool)>> { unsafe {
+		match self.swapchain_loader.acquire_next_image(
+			self.swapchain,
+			u64::MAX,
+			semaphore,
+			fence,
+		) {
+			Ok(acquired) => Ok(Some(acquired)),
+			Err(vk::Result::ERROR_OUT_OF_DATE_KHR) => Ok(None),
+			Err(error) => Err(error.into()),
+		}
+	}}
+
+	/// True when the swapchain is suboptimal or out of date and should be recreated.
+	pub fn present(
+		&self,
+		device: &Device,
+		image_index: u32,
+		wait_semaphores: &[vk::Semaphore],
+	) -> Result<bool> { unsafe {
+		let present_info = vk::PresentInfoKHR::builder()
+			.wait_semaphores(wait_semaphores)
+			.swapchains(std::slice::from_ref(&self.swapchain))
+			.image_indices(std::slice::from_ref(&image_index))
+			.build();
+		let _lock = device.lock_queue(&device.queues.present)?;
+		match self.swapchain_loader.queue_present(
+			self.present_queue,
+			&present_info,
+		) {
+			Ok(suboptimal) => Ok(suboptimal),
+			Err(vk::Result::ERROR_OUT_OF_DATE_KHR) => Ok(true),
+			Err(error) => Err(error.into()),
 		}
 	}}
+
+	/// The swapchain must no longer be in use by the device.
+	pub fn destroy(
+		&self,
+	) { unsafe {
+		self.swapchain_loader.destroy_swapchain(
+			self.swapchain,
+			None,
+		);
+	}}
 }
\ No newline at end of file
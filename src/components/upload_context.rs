@@ -0,0 +1,93 @@
+use std::sync::Mutex;
+
+use ash::vk;
+
+use crate::{Device, DeviceQueue, CommandPool, Fence, Result};
+
+/// Command pool and fence for one-time submits; submits from multiple threads are serialized.
+pub struct UploadContext {
+	pub command_pool: CommandPool,
+	pub fence: Fence,
+	lock: Mutex<()>,
+}
+
+impl UploadContext {
+	pub fn new(
+		device: &Device,
+		queue_family_index: u32,
+	) -> Result<Self> {
+		Ok(Self {
+			command_pool: CommandPool::for_family(
+				device,
+				queue_family_index,
+			)?,
+			fence: Fence::new(
+				device,
+			)?,
+			lock: Mutex::new(()),
+		})
+	}
+
+	/// Records `record` into a fresh command buffer, submits it to `queue` and waits for completion.
+	/// The command buffer is freed afterwards, also when recording fails.
+	pub fn submit<R>(
+		&self,
+		device: &Device,
+		queue: &DeviceQueue,
+		record: impl FnOnce(vk::CommandBuffer) -> Result<R>,
+	) -> Result<R> { unsafe {
+		let _lock = self.lock.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+		let command_buffer_info = vk::CommandBufferAllocateInfo::builder()
+			.command_pool(self.command_pool.command_pool)
+			.command_buffer_count(1)
+			.level(vk::CommandBufferLevel::PRIMARY)
+			.build();
+		let command_buffer = device.device.allocate_command_buffers(
+			&command_buffer_info,
+		)?[0];
+		let result = (|| {
+			let command_buffer_begin_info = vk::CommandBufferBeginInfo::builder()
+				.flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT)
+				.build();
+			device.device.begin_command_buffer(
+				command_buffer,
+				&command_buffer_begin_info,
+			)?;
+			let recorded = record(command_buffer);
+			device.device.end_command_buffer(
+				command_buffer,
+			)?;
+			let recorded = recorded?;
+			device.device.reset_fences(
+				&[self.fence.fence],
+			)?;
+			let submit_info = vk::SubmitInfo::builder()
+				.command_buffers(std::slice::from_ref(&command_buffer))
+				.build();
+			device.queue_submit(
+				queue,
+				&[submit_info],
+				self.fence.fence,
+			)?;
+			device.device.wait_for_fences(
+				&[self.fence.fence],
+				true,
+				u64::MAX,
+			)?;
+			Ok(recorded)
+		})();
+		device.device.free_command_buffers(
+			self.command_pool.command_pool,
+			&[command_buffer],
+		);
+		result
+	}}
+
+	pub fn destroy(
+		&self,
+		device: &Device,
+	) {
+		self.command_pool.destroy(device);
+		self.fence.destroy(device);
+	}
+}
\ No newline at end of file
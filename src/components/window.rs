@@ -3,6 +3,8 @@ use std::sync::mpsc::Receiver;
 use ash::vk;
 use glfw::{Context, WindowEvent, Glfw};
 
+use crate::{Result, VpbError};
+
 pub struct Window {
 	pub extent: vk::Extent2D,
 	pub glfw: Glfw,
@@ -13,24 +15,28 @@ pub struct Window {
 impl Window {
 	pub fn new(
 		title: &str,
-	) -> Self {
+	) -> Result<Self> {
 		let extent = vk::Extent2D {
 			width: 1280,
 			height: 720,
 		};
-		let mut glfw = glfw::init(glfw::FAIL_ON_ERRORS).unwrap();
+		let mut glfw = glfw::init(glfw::FAIL_ON_ERRORS).map_err(
+			|error| VpbError::Window(error.to_string())
+		)?;
 		glfw.window_hint(glfw::WindowHint::ClientApi(glfw::ClientApiHint::NoApi));
 		let (mut window, events) = glfw
 			.create_window(extent.width, extent.height, title, glfw::WindowMode::Windowed)
-			.expect("failed to create glfw window");
+			.ok_or(VpbError::Window("failed to create glfw window".to_string()))?;
 		// window.set_key_polling(true);
 		window.set_all_polling(true);
-		assert!(glfw.vulkan_supported());
-		Self {
+		if !glfw.vulkan_supported() {
+			return Err(VpbError::Window("glfw reports vulkan is not supported".to_string()));
+		}
+		Ok(Self {
 			extent,
 			glfw,
 			window,
 			events,
-		}
+		})
 	}
 }
\ No newline at end of file
@@ -0,0 +1,104 @@
+use std::fmt::Display;
+
+use ash::vk;
+use shaderc::ShaderKind;
+
+pub type Result<T> = std::result::Result<T, VpbError>;
+
+#[derive(Debug)]
+pub enum VpbError {
+	/// Vulkan call returned a non success result.
+	Vulkan(vk::Result),
+	/// VMA failed to allocate, map or free memory.
+	Allocator(vk_mem::Error),
+	/// File could not be read or written; contains the path.
+	Io(String, std::io::Error),
+	/// PNG could not be decoded; contains the path.
+	PngDecode(String, png::DecodingError),
+	/// Image was decoded but is not in a layout vpb can upload.
+	ImageFormat(String),
+	/// GLSL failed to compile; contains the path.
+	ShaderCompile(String, shaderc::Error),
+	ShaderCompilerUnavailable,
+	UnsupportedShaderKind(ShaderKind),
+	Window(String),
+	MissingInstanceExtension(String),
+	/// Application or engine name contains a NUL byte.
+	InvalidName(String),
+	NoSuitableDevice,
+	/// Device selected by name or index does not exist or is not suitable.
+	DeviceNotFound(String),
+	NoSuitableMemoryType,
+	NoSuitableDepthFormat,
+	/// Operation needs a headless program, see `ProgramData::new_headless`.
+	NoOffscreenTarget,
+	/// Render pass description references an attachment that does not exist.
+	InvalidRenderPass(String),
+	/// Pipeline description is inconsistent, e.g. descriptor sets are not numbered from 0.
+	InvalidPipeline(String),
+	/// Queue was not created by this device; contains its family index.
+	InvalidQueue(u32),
+	/// Object could not be destroyed because it is still shared; contains its name.
+	InUse(String),
+}
+
+impl Display for VpbError {
+	fn fmt(
+		&self,
+		f: &mut std::fmt::Formatter<'_>,
+	) -> std::fmt::Result {
+		match self {
+			VpbError::Vulkan(result) => write!(f, "vulkan error: {}", result),
+			VpbError::Allocator(error) => write!(f, "allocator error: {}", error),
+			VpbError::Io(path, error) => write!(f, "io error on \"{}\": {}", path, error),
+			VpbError::PngDecode(path, error) => write!(f, "failed to decode png \"{}\": {}", path, error),
+			VpbError::ImageFormat(message) => write!(f, "invalid image format: {}", message),
+			VpbError::ShaderCompile(path, error) => write!(f, "failed to compile \"{}\": {}", path, error),
+			VpbError::ShaderCompilerUnavailable => write!(f, "failed to create shader compiler"),
+			VpbError::UnsupportedShaderKind(kind) => write!(f, "unsupported shader kind {:?}", kind),
+			VpbError::Window(message) => write!(f, "window error: {}", message),
+			VpbError::MissingInstanceExtension(name) => write!(f, "instance extension \"{}\" is not available", name),
+			VpbError::InvalidName(name) => write!(f, "invalid name \"{}\"", name),
+			VpbError::NoSuitableDevice => write!(f, "failed to find a suitable physical device"),
+			VpbError::DeviceNotFound(selector) => write!(f, "selected physical device {} was not found", selector),
+			VpbError::NoSuitableMemoryType => write!(f, "failed to find a suitable memory type"),
+			VpbError::NoSuitableDepthFormat => write!(f, "none of the preferred depth formats are supported"),
+			VpbError::NoOffscreenTarget => write!(f, "program has no offscreen target"),
+			VpbError::InvalidRenderPass(message) => write!(f, "invalid render pass: {}", message),
+			VpbError::InvalidPipeline(message) => write!(f, "invalid pipeline: {}", message),
+			VpbError::InvalidQueue(family_index) => write!(f, "queue family {} does not belong to this device", family_index),
+			VpbError::InUse(name) => write!(f, "{} is still in use", name),
+		}
+	}
+}
+
+impl std::error::Error for VpbError {
+	fn source(
+		&self,
+	) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			VpbError::Vulkan(result) => Some(result),
+			VpbError::Allocator(error) => Some(error),
+			VpbError::Io(_, error) => Some(error),
+			VpbError::PngDecode(_, error) => Some(error),
+			VpbError::ShaderCompile(_, error) => Some(error),
+			_ => None,
+		}
+	}
+}
+
+impl From<vk::Result> for VpbError {
+	fn from(
+		result: vk::Result,
+	) -> Self {
+		VpbError::Vulkan(result)
+	}
+}
+
+impl From<vk_mem::Error> for VpbError {
+	fn from(
+		error: vk_mem::Error,
+	) -> Self {
+		VpbError::Allocator(error)
+	}
+}
\ No newline at end of file
@@ -0,0 +1,212 @@
+use ash::vk;
+
+use crate::{ProgramData, CommandBuffer, Semaphore, Result};
+
+/// Resources owned by one frame in flight.
+pub struct Frame {
+	/// `fence_submit` is signaled once the GPU has finished the frame.
+	pub command_buffer: CommandBuffer,
+	pub image_available: Semaphore,
+}
+
+/// Everything needed to record the frame returned by [`FrameLoop::begin_frame`].
+#[derive(Clone, Copy, Debug)]
+pub struct FrameContext {
+	/// Frame in flight, `0..frame_count`.
+	pub frame_index: usize,
+	/// Swapchain image, always 0 when headless.
+	pub image_index: u32,
+	pub image: vk::Image,
+	pub framebuffer: vk::Framebuffer,
+	pub extent: vk::Extent2D,
+	/// Already opened; closed by [`FrameLoop::end_frame`].
+	pub command_buffer: vk::CommandBuffer,
+}
+
+/// Acquire, record, submit and present for `frame_count` frames in flight.
+/// Headless programs render into the offscreen target and skip acquire and present.
+pub struct FrameLoop {
+	pub frames: Vec<Frame>,
+	frame_index: usize,
+	/// Image index and whether the swapchain reported suboptimal while acquiring.
+	acquired: Option<(u32, bool)>,
+	/// Fence of the frame last rendering into each swapchain image.
+	image_fences: Vec<vk::Fence>,
+	/// Signaled by the submit rendering into each swapchain image and waited on by its present.
+	/// Per image rather than per frame as frame fences do not cover presentation.
+	render_finished: Vec<Semaphore>,
+}
+
+impl FrameLoop {
+	pub fn new(
+		program_data: &ProgramData,
+	) -> Result<Self> {
+		let mut frames = Vec::with_capacity(program_data.frame_count);
+		for _ in 0..program_data.frame_count {
+			frames.push(Frame {
+				command_buffer: CommandBuffer::new(
+					&program_data.device,
+					&program_data.command_pool,
+				)?,
+				image_available: Semaphore::new(
+					&program_data.device,
+				)?,
+			});
+		}
+		Ok(Self {
+			frames,
+			frame_index: 0,
+			acquired: None,
+			image_fences: Vec::new(),
+			render_finished: Vec::new(),
+		})
+	}
+
+	/// Waits for this frame slot to be free, acquires a swapchain image and opens the command buffer.
+	/// Returns `None` when the swapchain was out of date (it is recreated) or the window is minimized;
+	/// skip rendering and call again next frame.
+	pub fn begin_frame(
+		&mut self,
+		program_data: &mut ProgramData,
+	) -> Result<Option<FrameContext>> { unsafe {
+		let device = program_data.device.clone();
+		let frame = &self.frames[self.frame_index];
+		device.device.wait_for_fences(
+			&[frame.command_buffer.fence_submit],
+			true,
+			u64::MAX,
+		)?;
+		program_data.end_frame_deletions(self.frame_index)?;
+		let (image_index, image, framebuffer) = match (
+			program_data.swapchain_state(),
+			&program_data.offscreen,
+		) {
+			(Some(state), _) => {
+				let (image_index, suboptimal) = match state.swapchain.acquire_next_image(
+					frame.image_available.semaphore,
+					vk::Fence::null(),
+				)? {
+					Some(acquired) => acquired,
+					None => {
+						drop(state);
+						program_data.recreate_swapchain()?;
+						return Ok(None);
+					},
+				};
+				let presentation = &state.presentation;
+				if self.image_fences.len() != presentation.images.len() {
+					self.image_fences = vec![vk::Fence::null(); presentation.images.len()];
+					// Presents of the previous images may still wait on their semaphores.
+					device.wait_idle()?;
+					for semaphore in self.render_finished.drain(..) {
+						semaphore.destroy(&device);
+					}
+					for _ in 0..presentation.images.len() {
+						self.render_finished.push(Semaphore::new(&device)?);
+					}
+				}
+				let image_fence = self.image_fences[image_index as usize];
+				if image_fence != vk::Fence::null() && image_fence != frame.command_buffer.fence_submit {
+					device.device.wait_for_fences(
+						&[image_fence],
+						true,
+						u64::MAX,
+					)?;
+				}
+				self.image_fences[image_index as usize] = frame.command_buffer.fence_submit;
+				self.acquired = Some((image_index, suboptimal));
+				(
+					image_index,
+					presentation.images[image_index as usize],
+					state.framebuffers.get(image_index),
+				)
+			},
+			(None, Some(offscreen)) => {
+				self.acquired = Some((0, false));
+				(
+					0,
+					offscreen.color_image,
+					offscreen.framebuffer,
+				)
+			},
+			_ => { return Ok(None); },
+		};
+		// Only reset once an image is acquired so an early return never leaves the fence unsignaled.
+		device.device.reset_fences(
+			&[frame.command_buffer.fence_submit],
+		)?;
+		frame.command_buffer.open(&device)?;
+		Ok(Some(FrameContext {
+			frame_index: self.frame_index,
+			image_index,
+			image,
+			framebuffer,
+			extent: program_data.extent(),
+			command_buffer: frame.command_buffer.command_buffer,
+		}))
+	}}
+
+	/// Closes and submits the frame's command buffer, presents it and advances to the next frame.
+	/// Recreates the swapchain when presenting reports it suboptimal or out of date.
+	pub fn end_frame(
+		&mut self,
+		program_data: &mut ProgramData,
+	) -> Result<()> {
+		let Some((image_index, suboptimal)) = self.acquired.take() else {
+			return Ok(());
+		};
+		let device = program_data.device.clone();
+		let frame = &self.frames[self.frame_index];
+		frame.command_buffer.close(&device)?;
+		let command_buffers = [frame.command_buffer.command_buffer];
+		let wait_semaphores = [frame.image_available.semaphore];
+		let wait_stages = [vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT];
+		let render_finished = match program_data.swapchain.is_some() {
+			true => Some(self.render_finished[image_index as usize].semaphore),
+			false => None,
+		};
+		let mut submit_info = vk::SubmitInfo::builder()
+			.command_buffers(&command_buffers);
+		if let Some(render_finished) = &render_finished {
+			submit_info = submit_info
+				.wait_semaphores(&wait_semaphores)
+				.wait_dst_stage_mask(&wait_stages)
+				.signal_semaphores(std::slice::from_ref(render_finished));
+		}
+		device.queue_submit(
+			&device.queues.graphics,
+			&[submit_info.build()],
+			frame.command_buffer.fence_submit,
+		)?;
+		self.frame_index = (self.frame_index + 1) % self.frames.len();
+		let recreate = match program_data.swapchain_state() {
+			Some(state) => state.swapchain.present(
+				&device,
+				image_index,
+				render_finished.as_slice(),
+			)?,
+			None => false,
+		};
+		if recreate || suboptimal {
+			program_data.recreate_swapchain()?;
+		}
+		Ok(())
+	}
+
+	/// The device must be idle.
+	pub fn destroy(
+		&self,
+		program_data: &ProgramData,
+	) {
+		for frame in self.frames.iter() {
+			frame.command_buffer.destroy(
+				&program_data.device,
+				&program_data.command_pool,
+			);
+			frame.image_available.destroy(&program_data.device);
+		}
+		for semaphore in self.render_finished.iter() {
+			semaphore.destroy(&program_data.device);
+		}
+	}
+}
\ No newline at end of file
@@ -1,14 +1,153 @@
-use ash::vk;
+use ash::vk::{self, MemoryPropertyFlags};
+use vk_mem::{AllocationCreateInfo, MemoryUsage, AllocationCreateFlags, Allocation};
 
-use crate::{Swapchain, Device, Instance, Window};
+use crate::{Swapchain, Device, Instance, RenderPass, Result, format_has_stencil};
+
+/// Color format of offscreen targets; UNORM so read back bytes are the written values.
+pub const OFFSCREEN_FORMAT: vk::Format = vk::Format::R8G8B8A8_UNORM;
+
+fn gpu_only_allocation_info(
+) -> AllocationCreateInfo {
+	AllocationCreateInfo {
+		usage: MemoryUsage::GpuOnly,
+		flags: AllocationCreateFlags::empty(),
+		required_flags: MemoryPropertyFlags::empty(),
+		preferred_flags: MemoryPropertyFlags::empty(),
+		memory_type_bits: 0,
+		pool: None,
+		user_data: None,
+	}
+}
+
+/// Color and depth target rendered into instead of a swapchain image (headless).
+pub struct OffscreenTarget {
+	pub extent: vk::Extent2D,
+	pub format: vk::Format,
+	pub color_image: vk::Image,
+	pub color_allocation: Allocation,
+	pub color_view: vk::ImageView,
+	/// Rendered into and resolved to `color_image` when the render pass is multisampled.
+	pub multisampled_color: Option<ColorImage>,
+	pub depth: DepthImage,
+	pub framebuffer: vk::Framebuffer,
+}
+
+impl OffscreenTarget {
+	pub fn new(
+		device: &Device,
+		allocator: &vk_mem::Allocator,
+		render_pass: &RenderPass,
+		extent: vk::Extent2D,
+		format: vk::Format,
+	) -> Result<Self> { unsafe {
+		let allocation_info = gpu_only_allocation_info();
+		let image_info = vk::ImageCreateInfo::builder()
+			.image_type(vk::ImageType::TYPE_2D)
+			.format(format)
+			.extent(extent.into())
+			.mip_levels(1)
+			.array_layers(1)
+			.samples(vk::SampleCountFlags::TYPE_1)
+			.tiling(vk::ImageTiling::OPTIMAL)
+			.usage(
+				vk::ImageUsageFlags::COLOR_ATTACHMENT |
+				vk::ImageUsageFlags::TRANSFER_SRC |
+				vk::ImageUsageFlags::SAMPLED
+			)
+			.sharing_mode(vk::SharingMode::EXCLUSIVE)
+			.initial_layout(vk::ImageLayout::UNDEFINED)
+			.build();
+		let (
+			color_image,
+			color_allocation,
+			_,
+		) = allocator.create_image(
+			&image_info,
+			&allocation_info,
+		)?;
+		let image_view_info = vk::ImageViewCreateInfo::builder()
+			.view_type(vk::ImageViewType::TYPE_2D)
+			.format(format)
+			.subresource_range(vk::ImageSubresourceRange {
+				aspect_mask: vk::ImageAspectFlags::COLOR,
+				base_mip_level: 0,
+				level_count: 1,
+				base_array_layer: 0,
+				layer_count: 1,
+			})
+			.image(color_image)
+			.build();
+		let color_view = device.device.create_image_view(
+			&image_view_info,
+			None,
+		)?;
+		let multisampled_color = ColorImage::multisampled(
+			device,
+			allocator,
+			&extent,
+			format,
+			render_pass.samples,
+		)?;
+		let depth = DepthImage::new(
+			device,
+			allocator,
+			&extent,
+			render_pass.samples,
+		)?;
+		let attachments = RenderPass::attachment_views(
+			color_view,
+			depth.view,
+			multisampled_color.as_ref().map(|color| color.view),
+		);
+		let framebuffer_info = vk::FramebufferCreateInfo::builder()
+			.render_pass(render_pass.render_pass)
+			.attachments(&attachments)
+			.width(extent.width)
+			.height(extent.height)
+			.layers(1)
+			.build();
+		let framebuffer = device.device.create_framebuffer(
+			&framebuffer_info,
+			None,
+		)?;
+		Ok(Self {
+			extent,
+			format,
+			color_image,
+			color_allocation,
+			color_view,
+			multisampled_color,
+			depth,
+			framebuffer,
+		})
+	}}
+
+	pub fn destroy(
+		&self,
+		device: &Device,
+		allocator: &vk_mem::Allocator,
+	) -> Result<()> { unsafe {
+		device.device.destroy_framebuffer(self.framebuffer, None);
+		if let Some(multisampled_color) = &self.multisampled_color {
+			multisampled_color.destroy(device, allocator)?;
+		}
+		self.depth.destroy(device, allocator)?;
+		device.device.destroy_image_view(self.color_view, None);
+		allocator.destroy_image(
+			self.color_image,
+			&self.color_allocation,
+		)?;
+		Ok(())
+	}}
+}
 
 pub fn create_presentation_images(
 	device: &Device,
 	swapchain: &Swapchain,
-) -> (Vec<vk::Image>, Vec<vk::ImageView>) { unsafe {
+) -> Result<(Vec<vk::Image>, Vec<vk::ImageView>)> { unsafe {
 	let images = swapchain.swapchain_loader.get_swapchain_images(
 		swapchain.swapchain,
-	).unwrap();
+	)?;
 	let mut image_views: Vec<vk::ImageView> = Vec::with_capacity(images.len());
 	for image in images.iter() {
 		let image_view_info = vk::ImageViewCreateInfo::builder()
@@ -32,73 +171,239 @@ pub fn create_presentation_images(
 		image_views.push(device.device.create_image_view(
 			&image_view_info,
 			None
-		).unwrap());
+		)?);
 	}
-	(images, image_views)
+	Ok((images, image_views))
 }}
 
-pub fn create_depth_image(
-	device: &Device,
-	window: &Window,
-) -> (vk::Image, vk::ImageView) { unsafe {
-	let image_info = vk::ImageCreateInfo::builder()
-		.image_type(vk::ImageType::TYPE_2D)
-		.format(vk::Format::D16_UNORM)
-		.extent(window.extent.into())
-		.mip_levels(1)
-		.array_layers(1)
-		.samples(vk::SampleCountFlags::TYPE_1)
-		.tiling(vk::ImageTiling::OPTIMAL)
-		.usage(vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT)
-		.sharing_mode(vk::SharingMode::EXCLUSIVE)
-		.build();
-	let image = device.device.create_image(
-		&image_info,
-		None
-	).unwrap();
-	let memory_requirements = device.device.get_image_memory_requirements(
-		image,
-	);
-	let memory_index: u32 = device.physical_device_memory_properties.memory_types[
-			..device.physical_device_memory_properties.memory_type_count as _
-		].iter().enumerate().find(
-			|(index, memory_type)| {
-				(1 << index) & memory_requirements.memory_type_bits != 0 &&
-				memory_type.property_flags & vk::MemoryPropertyFlags::DEVICE_LOCAL == vk::MemoryPropertyFlags::DEVICE_LOCAL
-			}
-		).map(
-			|(index, _)| {
-				index as _
-			}
-		).unwrap();
-	let memory_alloc_info = vk::MemoryAllocateInfo::builder()
-		.allocation_size(memory_requirements.size)
-		.memory_type_index(memory_index)
-		.build();
-	let memory_alloc = device.device.allocate_memory(
-		&memory_alloc_info,
-		None,
-	).unwrap();
-	device.device.bind_image_memory(
-		image,
-		memory_alloc,
-		0,
-	).unwrap();
-	let image_view_info = vk::ImageViewCreateInfo::builder()
-		.subresource_range(
-			vk::ImageSubresourceRange::builder()
-				.aspect_mask(vk::ImageAspectFlags::DEPTH)
-				.level_count(1)
-				.layer_count(1)
-				.build()
-		)
-		.image(image)
-		.format(image_info.format)
-		.view_type(vk::ImageViewType::TYPE_2D)
-		.build();
-	let image_view = device.device.create_image_view(
-		&image_view_info,
-		None,
-	).unwrap();
-	(image, image_view)
-}}
\ No newline at end of file
+/// Multisampled color attachment that is resolved into a single sampled image.
+pub struct ColorImage {
+	pub image: vk::Image,
+	pub allocation: Allocation,
+	pub view: vk::ImageView,
+	pub format: vk::Format,
+	pub samples: vk::SampleCountFlags,
+}
+
+impl ColorImage {
+	/// `None` when `samples` is `TYPE_1` and no separate image is needed.
+	pub fn multisampled(
+		device: &Device,
+		allocator: &vk_mem::Allocator,
+		extent: &vk::Extent2D,
+		format: vk::Format,
+		samples: vk::SampleCountFlags,
+	) -> Result<Option<Self>> { unsafe {
+		if samples == vk::SampleCountFlags::TYPE_1 {
+			return Ok(None);
+		}
+		let image_info = vk::ImageCreateInfo::builder()
+			.image_type(vk::ImageType::TYPE_2D)
+			.format(format)
+			.extent((*extent).into())
+			.mip_levels(1)
+			.array_layers(1)
+			.samples(samples)
+			.tiling(vk::ImageTiling::OPTIMAL)
+			.usage(
+				vk::ImageUsageFlags::COLOR_ATTACHMENT |
+				vk::ImageUsageFlags::TRANSIENT_ATTACHMENT
+			)
+			.sharing_mode(vk::SharingMode::EXCLUSIVE)
+			.build();
+		let (
+			image,
+			allocation,
+			_,
+		) = allocator.create_image(
+			&image_info,
+			&gpu_only_allocation_info(),
+		)?;
+		let image_view_info = vk::ImageViewCreateInfo::builder()
+			.subresource_range(
+				vk::ImageSubresourceRange::builder()
+					.aspect_mask(vk::ImageAspectFlags::COLOR)
+					.level_count(1)
+					.layer_count(1)
+					.build()
+			)
+			.image(image)
+			.format(format)
+			.view_type(vk::ImageViewType::TYPE_2D)
+			.build();
+		let view = device.device.create_image_view(
+			&image_view_info,
+			None,
+		)?;
+		Ok(Some(Self {
+			image,
+			allocation,
+			view,
+			format,
+			samples,
+		}))
+	}}
+
+	pub fn destroy(
+		&self,
+		device: &Device,
+		allocator: &vk_mem::Allocator,
+	) -> Result<()> { unsafe {
+		device.device.destroy_image_view(self.view, None);
+		allocator.destroy_image(self.image, &self.allocation)?;
+		Ok(())
+	}}
+}
+
+/// Depth (and stencil, when the format has one) attachment in `Device::depth_format`.
+pub struct DepthImage {
+	pub image: vk::Image,
+	pub allocation: Allocation,
+	pub view: vk::ImageView,
+	pub format: vk::Format,
+}
+
+impl DepthImage {
+	pub fn new(
+		device: &Device,
+		allocator: &vk_mem::Allocator,
+		extent: &vk::Extent2D,
+		samples: vk::SampleCountFlags,
+	) -> Result<Self> { unsafe {
+		let format = device.depth_format;
+		let image_info = vk::ImageCreateInfo::builder()
+			.image_type(vk::ImageType::TYPE_2D)
+			.format(format)
+			.extent((*extent).into())
+			.mip_levels(1)
+			.array_layers(1)
+			.samples(samples)
+			.tiling(vk::ImageTiling::OPTIMAL)
+			.usage(vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT)
+			.sharing_mode(vk::SharingMode::EXCLUSIVE)
+			.build();
+		let allocation_info = gpu_only_allocation_info();
+		let (
+			image,
+			allocation,
+			_,
+		) = allocator.create_image(
+			&image_info,
+			&allocation_info,
+		)?;
+		let image_view_info = vk::ImageViewCreateInfo::builder()
+			.subresource_range(
+				vk::ImageSubresourceRange::builder()
+					.aspect_mask(DepthImage::aspect_mask(format))
+					.level_count(1)
+					.layer_count(1)
+					.build()
+			)
+			.image(image)
+			.format(format)
+			.view_type(vk::ImageViewType::TYPE_2D)
+			.build();
+		let view = device.device.create_image_view(
+			&image_view_info,
+			None,
+		)?;
+		Ok(Self {
+			image,
+			allocation,
+			view,
+			format,
+		})
+	}}
+
+	/// `DEPTH`, plus `STENCIL` when `format` has a stencil aspect.
+	pub fn aspect_mask(
+		format: vk::Format,
+	) -> vk::ImageAspectFlags {
+		match format_has_stencil(format) {
+			true => vk::ImageAspectFlags::DEPTH | vk::ImageAspectFlags::STENCIL,
+			false => vk::ImageAspectFlags::DEPTH,
+		}
+	}
+
+	pub fn has_stencil(
+		&self,
+	) -> bool {
+		format_has_stencil(self.format)
+	}
+
+	pub fn destroy(
+		&self,
+		device: &Device,
+		allocator: &vk_mem::Allocator,
+	) -> Result<()> { unsafe {
+		device.device.destroy_image_view(self.view, None);
+		allocator.destroy_image(self.image, &self.allocation)?;
+		Ok(())
+	}}
+}
+
+/// Everything sized to the swapchain extent; rebuilt by `ProgramData::recreate_swapchain`.
+pub struct PresentationImages {
+	pub images: Vec<vk::Image>,
+	pub image_views: Vec<vk::ImageView>,
+	/// Rendered into and resolved to the swapchain images when multisampling.
+	pub multisampled_color: Option<ColorImage>,
+	pub depth: DepthImage,
+	pub extent: vk::Extent2D,
+	/// Incremented on every rebuild so dependents such as [`Framebuffers`](crate::Framebuffers) can tell they are stale.
+	pub generation: u64,
+}
+
+impl PresentationImages {
+	pub fn new(
+		device: &Device,
+		allocator: &vk_mem::Allocator,
+		swapchain: &Swapchain,
+		samples: vk::SampleCountFlags,
+		generation: u64,
+	) -> Result<Self> {
+		let (
+			images,
+			image_views,
+		) = create_presentation_images(
+			device,
+			swapchain,
+		)?;
+		let multisampled_color = ColorImage::multisampled(
+			device,
+			allocator,
+			&swapchain.extent,
+			swapchain.surface_format.format,
+			samples,
+		)?;
+		let depth = DepthImage::new(
+			device,
+			allocator,
+			&swapchain.extent,
+			samples,
+		)?;
+		Ok(Self {
+			images,
+			image_views,
+			multisampled_color,
+			depth,
+			extent: swapchain.extent,
+			generation,
+		})
+	}
+
+	/// Swapchain images are owned by the swapchain and are not destroyed here.
+	pub fn destroy(
+		&self,
+		device: &Device,
+		allocator: &vk_mem::Allocator,
+	) -> Result<()> { unsafe {
+		for image_view in self.image_views.iter() {
+			device.device.destroy_image_view(*image_view, None);
+		}
+		if let Some(multisampled_color) = &self.multisampled_color {
+			multisampled_color.destroy(device, allocator)?;
+		}
+		self.depth.destroy(device, allocator)
+	}}
+}
\ No newline at end of file
@@ -1,4 +1,6 @@
-use std::{fs, str::FromStr};
+use std::fs;
+
+use crate::{Result, VpbError};
 
 pub struct ImageArrayState {
 	pub path: String,
@@ -8,10 +10,12 @@ pub struct ImageArrayState {
 impl ImageArrayState {
 	pub fn load(
 		path_abs: &str,
-	) -> Self {
-		let mut ias_path = String::from_str(path_abs).unwrap();
+	) -> Result<Self> {
+		let mut ias_path = path_abs.to_string();
 		ias_path += "/ias.txt";
-		let ias = fs::read(ias_path).unwrap();
+		let ias = fs::read(&ias_path).map_err(
+			|error| VpbError::Io(ias_path.clone(), error)
+		)?;
 		let mut mid_buffer = String::with_capacity(32);
 		let mut textures = Vec::with_capacity(1024);
 		for c in ias {
@@ -26,22 +30,24 @@ impl ImageArrayState {
 				},
 			}
 		}
-		Self {
-			path: String::from_str(path_abs).unwrap(),
+		Ok(Self {
+			path: path_abs.to_string(),
 			textures,
-		}
+		})
 	}
 	
 	pub fn store(
 		self,
-	) {
-		let mut ias_path = String::from_str(&self.path).unwrap();
+	) -> Result<()> {
+		let mut ias_path = self.path.clone();
 		ias_path += "/ias.txt";
 		let mut ias: Vec<u8> = Vec::with_capacity(1024);
 		for texture in self.textures {
 			ias.extend_from_slice(texture.as_bytes());
 			ias.extend_one('\n' as u8);
 		}
-		fs::write(ias_path, ias).expect("failed to write IAS");
+		fs::write(&ias_path, ias).map_err(
+			|error| VpbError::Io(ias_path.clone(), error)
+		)
 	}
 }
\ No newline at end of file
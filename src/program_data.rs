@@ -1,80 +1,627 @@
-use std::{sync::Arc, fs::File, io::Read};
+use std::{sync::{Arc, RwLock, RwLockReadGuard}, path::PathBuf};
 
 use ash::vk::{self, DeviceMemory};
-use shaderc::{ShaderKind, CompileOptions};
+use shaderc::ShaderKind;
+use vk_mem::{AllocationCreateInfo, MemoryUsage, AllocationCreateFlags};
 
-use crate::{Window, Instance, Surface, Device, Swapchain, RenderPass, DescriptorPool, CommandPool, CommandBuffer, ShaderLoader};
+use crate::{Window, Instance, Surface, Device, Swapchain, RenderPass, DescriptorPool, CommandPool, CommandBuffer, UploadContext, DeletionQueue, ShaderLoader, OffscreenTarget, PresentationImages, Framebuffers, SwapchainConfig, RenderPassConfig, SWAPCHAIN_COLORSPACE_EXTENSION, InstanceConfig, DeviceConfig, Result, VpbError, OFFSCREEN_FORMAT};
 
+const ENGINE_NAME: &str = "vpb";
+/// Directory `ProgramData::load_shader` reads GLSL from.
+pub const SHADER_ROOT: &str = "res/shaders/";
+
+/// GLSL source of shader `name`; the extension follows the stage.
+pub fn shader_source_path(
+	shader_kind: ShaderKind,
+	name: &str,
+) -> Result<String> {
+	Ok((SHADER_ROOT.to_string() + name) + match shader_kind {
+		ShaderKind::Vertex => ".vert",
+		ShaderKind::Fragment => ".frag",
+		ShaderKind::Compute => ".comp",
+		_ => { return Err(VpbError::UnsupportedShaderKind(shader_kind)); }
+	})
+}
+
+pub struct ProgramDataConfig {
+	pub frame_count: usize,
+	/// MSAA sample count; clamped to what the device supports.
+	pub samples: vk::SampleCountFlags,
+	pub instance: InstanceConfig,
+	pub device: DeviceConfig,
+	pub swapchain: SwapchainConfig,
+	pub render_pass: RenderPassConfig,
+	/// Searched for shader `#include`s after [`SHADER_ROOT`].
+	pub shader_search_paths: Vec<PathBuf>,
+}
+
+impl Default for ProgramDataConfig {
+	fn default() -> Self {
+		Self {
+			frame_count: 2,
+			samples: vk::SampleCountFlags::TYPE_1,
+			instance: InstanceConfig::default(),
+			device: DeviceConfig::default(),
+			swapchain: SwapchainConfig::default(),
+			render_pass: RenderPassConfig::default(),
+			shader_search_paths: Vec::new(),
+		}
+	}
+}
+
+/// Swapchain and what is built over its images; replaced as a whole by [`ProgramData::recreate_swapchain`].
+pub struct SwapchainState {
+	pub swapchain: Swapchain,
+	/// Applied on every swapchain (re)creation.
+	pub config: SwapchainConfig,
+	/// Swapchain views and depth image.
+	pub presentation: PresentationImages,
+	/// Framebuffers of `render_pass` over `presentation`.
+	pub framebuffers: Framebuffers,
+}
+
+/// Clones share every object. The swapchain state is replaced behind a lock, so every clone sees
+/// the recreated swapchain; other objects are never replaced.
+/// Deferred deletions are only destroyed by [`crate::FrameLoop::begin_frame`]; render loops
+/// without a `FrameLoop` must call [`ProgramData::end_frame_deletions`] every frame.
 #[derive(Clone)]
 pub struct ProgramData {
-	pub allocator: Arc<Option<Arc<vk_mem::Allocator>>>,
-	pub window: Arc<Window>,
+	pub allocator: Arc<vk_mem::Allocator>,
+	/// `None` when headless.
+	pub window: Option<Arc<Window>>,
 	pub instance: Arc<Instance>,
-	pub surface: Arc<Surface>,
+	/// `None` when headless.
+	pub surface: Option<Arc<Surface>>,
 	pub device: Arc<Device>,
-	pub swapchain: Arc<Swapchain>,
+	/// `None` when headless; read through [`ProgramData::swapchain_state`].
+	pub swapchain: Option<Arc<RwLock<SwapchainState>>>,
+	/// Render target used instead of the swapchain when headless.
+	pub offscreen: Option<Arc<OffscreenTarget>>,
 	pub render_pass: Arc<RenderPass>,
 	pub descriptor_pool: Arc<DescriptorPool>,
 	pub command_pool: Arc<CommandPool>,
 	pub command_buffer_setup: Arc<CommandBuffer>,
 	pub command_buffer_draw: Arc<CommandBuffer>,
+	/// Used by [`ProgramData::immediate_submit`].
+	pub upload_context: Arc<UploadContext>,
+	/// One-time submits on the transfer queue, see [`ProgramData::immediate_transfer`].
+	pub transfer_context: Arc<UploadContext>,
+	/// Resources destroyed once the frames that may use them have finished.
+	pub deletion_queue: Arc<DeletionQueue>,
 	pub shader_loader: Arc<ShaderLoader>,
 	pub frame_count: usize,
 }
 
 impl ProgramData {
+	pub fn new(
+		name: &str,
+		mut window: Window,
+		mut config: ProgramDataConfig,
+	) -> Result<Self> {
+		if config.swapchain.needs_colorspace_extension() {
+			config.instance.optional_extensions.push(SWAPCHAIN_COLORSPACE_EXTENSION.to_string());
+		}
+		let instance = Instance::new(
+			name,
+			ENGINE_NAME,
+			Some(&window),
+			config.instance,
+		)?;
+		let surface = Surface::new(
+			&instance,
+			&window,
+		)?;
+		let device = Device::new(
+			&instance,
+			Some(&surface),
+			&config.device,
+		)?;
+		let swapchain = Swapchain::new(
+			&instance,
+			&mut window,
+			&surface,
+			&device,
+			&config.swapchain,
+		)?;
+		let render_pass = RenderPass::new(
+			&device,
+			swapchain.surface_format.format,
+			vk::ImageLayout::PRESENT_SRC_KHR,
+			device.clamp_samples(config.samples),
+			&config.render_pass,
+		)?;
+		let mut program_data = ProgramData::assemble(
+			instance,
+			device,
+			render_pass,
+			config.frame_count,
+			config.shader_search_paths,
+		)?;
+		let presentation = PresentationImages::new(
+			&program_data.device,
+			program_data.get_allocator(),
+			&swapchain,
+			program_data.render_pass.samples,
+			0,
+		)?;
+		let framebuffers = Framebuffers::new(
+			&program_data.device,
+			&program_data.render_pass,
+			&presentation,
+		)?;
+		program_data.window = Some(Arc::new(window));
+		program_data.surface = Some(Arc::new(surface));
+		program_data.swapchain = Some(Arc::new(RwLock::new(SwapchainState {
+			swapchain,
+			config: config.swapchain,
+			presentation,
+			framebuffers,
+		})));
+		Ok(program_data)
+	}
+
+	/// Renders into an [`OffscreenTarget`] of `extent`; no window, surface or swapchain is created.
+	pub fn new_headless(
+		name: &str,
+		extent: vk::Extent2D,
+		config: ProgramDataConfig,
+	) -> Result<Self> {
+		let instance = Instance::new(
+			name,
+			ENGINE_NAME,
+			None,
+			config.instance,
+		)?;
+		let device = Device::new(
+			&instance,
+			None,
+			&config.device,
+		)?;
+		let render_pass = RenderPass::new(
+			&device,
+			OFFSCREEN_FORMAT,
+			vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+			device.clamp_samples(config.samples),
+			&config.render_pass,
+		)?;
+		let mut program_data = ProgramData::assemble(
+			instance,
+			device,
+			render_pass,
+			config.frame_count,
+			config.shader_search_paths,
+		)?;
+		let offscreen = OffscreenTarget::new(
+			&program_data.device,
+			program_data.get_allocator(),
+			&program_data.render_pass,
+			extent,
+			OFFSCREEN_FORMAT,
+		)?;
+		program_data.offscreen = Some(Arc::new(offscreen));
+		Ok(program_data)
+	}
+
+	fn assemble(
+		instance: Instance,
+		device: Device,
+		render_pass: RenderPass,
+		frame_count: usize,
+		shader_search_paths: Vec<PathBuf>,
+	) -> Result<Self> {
+		let allocator = ProgramData::create_allocator(
+			instance.instance.clone(),
+			device.device.clone(),
+			device.physical_device,
+			frame_count,
+		)?;
+		let descriptor_pool = DescriptorPool::new(
+			&device,
+			frame_count,
+		)?;
+		let command_pool = CommandPool::new(
+			&device,
+		)?;
+		let command_buffer_setup = CommandBuffer::new(
+			&device,
+			&command_pool,
+		)?;
+		let command_buffer_draw = CommandBuffer::new(
+			&device,
+			&command_pool,
+		)?;
+		let upload_context = UploadContext::new(
+			&device,
+			device.queues.graphics.family_index,
+		)?;
+		let transfer_context = UploadContext::new(
+			&device,
+			device.queues.transfer.family_index,
+		)?;
+		let shader_loader = ShaderLoader::new(shader_search_paths)?;
+		Ok(Self {
+			allocator,
+			window: None,
+			instance: Arc::new(instance),
+			surface: None,
+			device: Arc::new(device),
+			swapchain: None,
+			offscreen: None,
+			render_pass: Arc::new(render_pass),
+			descriptor_pool: Arc::new(descriptor_pool),
+			command_pool: Arc::new(command_pool),
+			command_buffer_setup: Arc::new(command_buffer_setup),
+			command_buffer_draw: Arc::new(command_buffer_draw),
+			upload_context: Arc::new(upload_context),
+			transfer_context: Arc::new(transfer_context),
+			deletion_queue: Arc::new(DeletionQueue::new(frame_count)),
+			shader_loader: Arc::new(shader_loader),
+			frame_count,
+		})
+	}
+
+	/// Waits for the device to be idle and destroys every object in reverse creation order.
+	/// Buffers, block states and [`FrameLoop`](crate::FrameLoop)s must be destroyed first and other
+	/// clones of `self` must not be used afterwards. Fails before destroying anything while another
+	/// clone still holds the allocator.
+	pub fn destroy(
+		self,
+	) -> Result<()> {
+		self.device.wait_idle()?;
+		let allocator = Arc::try_unwrap(self.allocator).map_err(
+			|_| VpbError::InUse("allocator".to_string())
+		)?;
+		self.deletion_queue.flush(
+			&self.device,
+			&allocator,
+		)?;
+		if let Some(state) = &self.swapchain {
+			let state = state.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+			state.framebuffers.destroy(&self.device);
+			state.presentation.destroy(
+				&self.device,
+				&allocator,
+			)?;
+			state.swapchain.destroy();
+		}
+		if let Some(offscreen) = &self.offscreen {
+			offscreen.destroy(
+				&self.device,
+				&allocator,
+			)?;
+		}
+		self.command_buffer_setup.destroy(&self.device, &self.command_pool);
+		self.command_buffer_draw.destroy(&self.device, &self.command_pool);
+		self.command_pool.destroy(&self.device);
+		self.upload_context.destroy(&self.device);
+		self.transfer_context.destroy(&self.device);
+		self.descriptor_pool.destroy(&self.device);
+		self.render_pass.destroy(&self.device);
+		drop(allocator);
+		self.device.destroy();
+		if let Some(surface) = &self.surface {
+			surface.destroy(&self.instance);
+		}
+		self.instance.destroy();
+		Ok(())
+	}
+
+	pub fn is_headless(
+		&self,
+	) -> bool {
+		self.swapchain.is_none()
+	}
+
+	/// `None` when headless. Drop the guard before [`ProgramData::recreate_swapchain`], which
+	/// waits for it.
+	pub fn swapchain_state(
+		&self,
+	) -> Option<RwLockReadGuard<'_, SwapchainState>> {
+		self.swapchain.as_ref().map(
+			|state|
+			state.read().unwrap_or_else(|poisoned| poisoned.into_inner())
+		)
+	}
+
+	/// Extent of the swapchain, or of the offscreen target when headless.
+	pub fn extent(
+		&self,
+	) -> vk::Extent2D {
+		match (self.swapchain_state(), &self.offscreen) {
+			(Some(state), _) => state.swapchain.extent,
+			(None, Some(offscreen)) => offscreen.extent,
+			(None, None) => vk::Extent2D::default(),
+		}
+	}
+
+	/// Rebuilds the swapchain, presentation images and `framebuffers` after a resize, `ERROR_OUT_OF_DATE_KHR`
+	/// or `SUBOPTIMAL_KHR`. Waits for the device to be idle. Returns false when headless or while
+	/// the window is minimized, in which case nothing changes and the call should be retried later.
+	/// On error the previous objects are kept and the call can be retried.
+	/// Every clone of `self` sees the new objects. Other [`Framebuffers`] must be brought up to date
+	/// with [`Framebuffers::update`].
+	pub fn recreate_swapchain(
+		&self,
+	) -> Result<bool> {
+		let (
+			Some(window),
+			Some(surface),
+			Some(state),
+		) = (
+			&self.window,
+			&self.surface,
+			&self.swapchain,
+		) else {
+			return Ok(false);
+		};
+		let mut state = state.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+		let swapchain_new = match state.swapchain.recreate(
+			window,
+			surface,
+			&self.device,
+			&state.config,
+		)? {
+			Some(swapchain_new) => swapchain_new,
+			None => { return Ok(false); },
+		};
+		// Everything new is built before the old objects go, so a failure leaves a state the next call can retry from.
+		let presentation_new = match PresentationImages::new(
+			&self.device,
+			self.get_allocator(),
+			&swapchain_new,
+			self.render_pass.samples,
+			state.presentation.generation + 1,
+		) {
+			Ok(presentation_new) => presentation_new,
+			Err(error) => {
+				swapchain_new.destroy();
+				return Err(error);
+			},
+		};
+		let framebuffers_new = match Framebuffers::new(
+			&self.device,
+			&self.render_pass,
+			&presentation_new,
+		) {
+			Ok(framebuffers_new) => framebuffers_new,
+			Err(error) => {
+				presentation_new.destroy(
+					&self.device,
+					self.get_allocator(),
+				)?;
+				swapchain_new.destroy();
+				return Err(error);
+			},
+		};
+		self.device.wait_idle()?;
+		let config = state.config.clone();
+		let state_old = std::mem::replace(&mut *state, SwapchainState {
+			swapchain: swapchain_new,
+			config,
+			presentation: presentation_new,
+			framebuffers: framebuffers_new,
+		});
+		state_old.framebuffers.destroy(&self.device);
+		state_old.presentation.destroy(
+			&self.device,
+			self.get_allocator(),
+		)?;
+		state_old.swapchain.destroy();
+		Ok(true)
+	}
+
+	/// Stores `config` and recreates the swapchain with it; see [`ProgramData::recreate_swapchain`].
+	/// Does nothing when headless.
+	pub fn set_swapchain_config(
+		&self,
+		config: SwapchainConfig,
+	) -> Result<bool> {
+		let Some(state) = &self.swapchain else {
+			return Ok(false);
+		};
+		state.write().unwrap_or_else(|poisoned| poisoned.into_inner()).config = config;
+		self.recreate_swapchain()
+	}
+
+	/// Only the present modes change; preferred surface formats are kept.
+	pub fn set_vsync(
+		&self,
+		enabled: bool,
+	) -> Result<bool> {
+		let Some(state) = &self.swapchain else {
+			return Ok(false);
+		};
+		state.write().unwrap_or_else(|poisoned| poisoned.into_inner()).config.present_modes =
+			SwapchainConfig::vsync(enabled).present_modes;
+		self.recreate_swapchain()
+	}
+
 	pub fn get_allocator(
 		&self,
 	) -> &vk_mem::Allocator {
-		&self.allocator.as_ref().as_ref().unwrap()
+		&self.allocator
+	}
+
+	/// Destroys what was deferred the last time `frame_index` was current and makes it current.
+	/// Call once the fence of `frame_index` has been waited on, before recording into it;
+	/// [`crate::FrameLoop`] does this itself.
+	pub fn end_frame_deletions(
+		&self,
+		frame_index: usize,
+	) -> Result<()> {
+		self.deletion_queue.begin_frame(
+			frame_index,
+			&self.device,
+			self.get_allocator(),
+		)
 	}
 
+	/// Records `record` into a one-time command buffer, submits it to the graphics queue and
+	/// waits for it to finish, so staging resources may be freed as soon as this returns.
+	pub fn immediate_submit<R>(
+		&self,
+		record: impl FnOnce(vk::CommandBuffer) -> Result<R>,
+	) -> Result<R> {
+		self.upload_context.submit(
+			&self.device,
+			&self.device.queues.graphics,
+			record,
+		)
+	}
+
+	/// Same as [`ProgramData::immediate_submit`] on the transfer queue, for uploads.
+	/// Exclusive resources read by rendering afterwards must be released in `record` with
+	/// [`crate::DeviceQueues::upload_release`] and acquired through `immediate_submit`.
+	pub fn immediate_transfer<R>(
+		&self,
+		record: impl FnOnce(vk::CommandBuffer) -> Result<R>,
+	) -> Result<R> {
+		self.transfer_context.submit(
+			&self.device,
+			&self.device.queues.transfer,
+			record,
+		)
+	}
+
+	/// Copies the offscreen color image into host memory; tightly packed rows of [`OFFSCREEN_FORMAT`] texels.
+	/// Call once a frame has been submitted, the image is expected in `TRANSFER_SRC_OPTIMAL`.
+	pub fn read_offscreen(
+		&self,
+	) -> Result<Vec<u8>> { unsafe {
+		let offscreen = self.offscreen.as_ref().ok_or(VpbError::NoOffscreenTarget)?;
+		let size = (offscreen.extent.width * offscreen.extent.height * 4) as usize;
+		let allocation_info = AllocationCreateInfo {
+			usage: MemoryUsage::GpuToCpu,
+			flags: AllocationCreateFlags::MAPPED,
+			required_flags: vk::MemoryPropertyFlags::empty(),
+			preferred_flags: vk::MemoryPropertyFlags::empty(),
+			memory_type_bits: 0,
+			pool: None,
+			user_data: None,
+		};
+		let buffer_info = vk::BufferCreateInfo::builder()
+			.sharing_mode(vk::SharingMode::EXCLUSIVE)
+			.size(size as u64)
+			.usage(vk::BufferUsageFlags::TRANSFER_DST)
+			.build();
+		let (
+			buffer,
+			buffer_allocation,
+			buffer_allocation_info,
+		) = self.allocator.create_buffer(
+			&buffer_info,
+			&allocation_info,
+		)?;
+		let copied = self.immediate_submit(|command_buffer| {
+			// Submissions on the queue are ordered but their memory accesses are not.
+			let image_barrier = vk::ImageMemoryBarrier::builder()
+				.old_layout(vk::ImageLayout::TRANSFER_SRC_OPTIMAL)
+				.new_layout(vk::ImageLayout::TRANSFER_SRC_OPTIMAL)
+				.src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+				.dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+				.image(offscreen.color_image)
+				.subresource_range(vk::ImageSubresourceRange {
+					aspect_mask: vk::ImageAspectFlags::COLOR,
+					base_mip_level: 0,
+					level_count: 1,
+					base_array_layer: 0,
+					layer_count: 1,
+				})
+				.src_access_mask(vk::AccessFlags::COLOR_ATTACHMENT_WRITE)
+				.dst_access_mask(vk::AccessFlags::TRANSFER_READ)
+				.build();
+			self.device.device.cmd_pipeline_barrier(
+				command_buffer,
+				vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+				vk::PipelineStageFlags::TRANSFER,
+				vk::DependencyFlags::empty(),
+				&[],
+				&[],
+				&[image_barrier],
+			);
+			let region = vk::BufferImageCopy::builder()
+				.image_subresource(vk::ImageSubresourceLayers {
+					aspect_mask: vk::ImageAspectFlags::COLOR,
+					mip_level: 0,
+					base_array_layer: 0,
+					layer_count: 1,
+				})
+				.image_extent(offscreen.extent.into())
+				.build();
+			self.device.device.cmd_copy_image_to_buffer(
+				command_buffer,
+				offscreen.color_image,
+				vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+				buffer,
+				&[region],
+			);
+			let buffer_barrier = vk::BufferMemoryBarrier::builder()
+				.src_access_mask(vk::AccessFlags::TRANSFER_WRITE)
+				.dst_access_mask(vk::AccessFlags::HOST_READ)
+				.src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+				.dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+				.buffer(buffer)
+				.offset(0)
+				.size(vk::WHOLE_SIZE)
+				.build();
+			self.device.device.cmd_pipeline_barrier(
+				command_buffer,
+				vk::PipelineStageFlags::TRANSFER,
+				vk::PipelineStageFlags::HOST,
+				vk::DependencyFlags::empty(),
+				&[],
+				&[buffer_barrier],
+				&[],
+			);
+			Ok(())
+		}).and_then(|_| {
+			self.allocator.invalidate_allocation(
+				&buffer_allocation,
+				0,
+				vk::WHOLE_SIZE as usize,
+			)?;
+			let mapped = buffer_allocation_info.get_mapped_data();
+			Ok(std::slice::from_raw_parts(mapped, size).to_vec())
+		});
+		self.allocator.destroy_buffer(
+			buffer,
+			&buffer_allocation,
+		)?;
+		copied
+	}}
+
 	pub fn load_shader(
 		&self,
 		shader_kind: ShaderKind,
 		name: &str,
-	) -> vk::ShaderModule { unsafe {
-		let options = CompileOptions::new().unwrap();
-		let glsl_path = ("res/shaders/".to_string() + name) + match shader_kind {
-			ShaderKind::Vertex => ".vert",
-			ShaderKind::Fragment => ".frag",
-			ShaderKind::Compute => ".comp",
-			_ => { panic!("not impl"); }
-		};
-		let glsl_path = glsl_path.as_str();
-		let spv_path = ("res/shaders/".to_string() + name) + ".spv";
-		let spv_path = spv_path.as_str();
-		let mut file = File::open(glsl_path).expect(
-			format!("shader \"{}\" does not exist", glsl_path).as_str()
-		);
-		let mut text: String = String::with_capacity(1024);
-		file.read_to_string(&mut text).unwrap();
-		let binary_artifact = self.shader_loader.compiler.compile_into_spirv(
-			text.as_str(),
+	) -> Result<vk::ShaderModule> {
+		self.load_shader_with_defines(
 			shader_kind,
-			glsl_path, "main",
-			Some(&options),
-		).expect(format!("failed to compile \"{}\"", glsl_path).as_str());
-		debug_assert_eq!(Some(&0x07230203), binary_artifact.as_binary().first());
-		// let text_artifact = shader_loader.compiler.compile_into_spirv_assembly(
-		// 	text.as_str(),
-		// 	shader_kind,
-		// 	glsl_path, "main",
-		// 	Some(&shader_loader.options),
-		// ).expect(format!("failed to compile \"{}\"", glsl_path).as_str());
-		// debug_assert!(text_artifact.as_text().starts_with("; SPIR-V\n"));
-		// let mut spv_file = File::open(spv_path).unwrap();
-		// let spv_text = read_spv(&mut spv_file).unwrap();
-	
-		let spv_text = binary_artifact.as_binary();
-	
+			name,
+			&[],
+		)
+	}
+
+	/// See [`ShaderLoader::compile`] for how `defines` are applied.
+	pub fn load_shader_with_defines(
+		&self,
+		shader_kind: ShaderKind,
+		name: &str,
+		defines: &[(&str, Option<&str>)],
+	) -> Result<vk::ShaderModule> { unsafe {
+		let glsl_path = shader_source_path(shader_kind, name)?;
+		let spirv = self.shader_loader.compile(
+			shader_kind,
+			glsl_path.as_str(),
+			defines,
+		)?;
 		let shader_info = vk::ShaderModuleCreateInfo::builder()
-			.code(spv_text)
+			.code(spirv.as_slice())
 			.build();
-		self.device.device.create_shader_module(
+		Ok(self.device.device.create_shader_module(
 			&shader_info,
 			None,
-		).unwrap()
+		)?)
 	}}
 
 	pub fn create_allocator(
@@ -82,7 +629,7 @@ impl ProgramData {
 		device: ash::Device,
 		physical_device: vk::PhysicalDevice,
 		frame_count: usize,
-	) -> Arc<vk_mem::Allocator> { unsafe {
+	) -> Result<Arc<vk_mem::Allocator>> { unsafe {
 		let allocator_info = vk_mem::AllocatorCreateInfo {
 			physical_device,
 			device,
@@ -92,10 +639,10 @@ impl ProgramData {
 			frame_in_use_count: frame_count as u32,
 			heap_size_limits: None,
 		};
-		Arc::new(
+		Ok(Arc::new(
 			vk_mem::Allocator::new(
 				&allocator_info,
-			).expect("failed to create allocator")
-		)
+			)?
+		))
 	}}
 }
\ No newline at end of file
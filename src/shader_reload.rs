@@ -0,0 +1,321 @@
+use std::{collections::HashMap, fs, path::{Path, PathBuf}, time::SystemTime};
+
+use ash::vk;
+use shaderc::ShaderKind;
+
+use crate::{ProgramData, RenderPass, BlockSpawnerGen, GraphicsPipelineDesc, GraphicsPipeline, ComputePipelineDesc, ComputePipeline, Deletion, Result, VpbError, SHADER_ROOT, shader_source_path};
+
+/// Stage as passed to `ProgramData::load_shader_with_defines`: kind, name and defines.
+pub type HotShader = (ShaderKind, String, Vec<(String, Option<String>)>);
+
+/// Pipeline whose shaders can be recompiled while the program runs.
+pub trait ShaderReload {
+	fn shaders(
+		&self,
+	) -> &[HotShader];
+
+	/// Recompiles every stage and swaps in a new pipeline; the old one goes through the deletion queue.
+	/// On error the current pipeline is kept.
+	fn reload(
+		&mut self,
+		program_data: &ProgramData,
+	) -> Result<()>;
+}
+
+fn shader_stage(
+	shader_kind: ShaderKind,
+) -> Result<vk::ShaderStageFlags> {
+	match shader_kind {
+		ShaderKind::Vertex => Ok(vk::ShaderStageFlags::VERTEX),
+		ShaderKind::Fragment => Ok(vk::ShaderStageFlags::FRAGMENT),
+		ShaderKind::Compute => Ok(vk::ShaderStageFlags::COMPUTE),
+		_ => Err(VpbError::UnsupportedShaderKind(shader_kind)),
+	}
+}
+
+fn hot_shader(
+	shader_kind: ShaderKind,
+	name: &str,
+	defines: &[(&str, Option<&str>)],
+) -> HotShader {
+	(
+		shader_kind,
+		name.to_string(),
+		defines.iter().map(
+			|(name, value)|
+			(name.to_string(), value.map(|value| value.to_string()))
+		).collect(),
+	)
+}
+
+/// Compiles every stage; nothing is left alive when one fails.
+fn load_stages(
+	program_data: &ProgramData,
+	shaders: &[HotShader],
+) -> Result<Vec<(vk::ShaderModule, vk::ShaderStageFlags)>> {
+	let mut stages = Vec::with_capacity(shaders.len());
+	for (shader_kind, name, defines) in shaders.iter() {
+		let defines: Vec<(&str, Option<&str>)> = defines.iter().map(
+			|(name, value)|
+			(name.as_str(), value.as_deref())
+		).collect();
+		let stage = shader_stage(*shader_kind).and_then(
+			|stage|
+			Ok((program_data.load_shader_with_defines(*shader_kind, name, &defines)?, stage))
+		);
+		match stage {
+			Ok(stage) => stages.push(stage),
+			Err(error) => {
+				destroy_stages(program_data, &stages);
+				return Err(error);
+			},
+		}
+	}
+	Ok(stages)
+}
+
+fn destroy_stages(
+	program_data: &ProgramData,
+	stages: &[(vk::ShaderModule, vk::ShaderStageFlags)],
+) { unsafe {
+	for (shader_module, _) in stages.iter() {
+		program_data.device.device.destroy_shader_module(*shader_module, None);
+	}
+}}
+
+/// [`GraphicsPipeline`] that keeps what it needs to be rebuilt from its shader sources.
+pub struct HotGraphicsPipeline {
+	pub pipeline: GraphicsPipeline,
+	desc: GraphicsPipelineDesc,
+	shaders: Vec<HotShader>,
+	render_pass: vk::RenderPass,
+	samples: vk::SampleCountFlags,
+	color_attachment_count: u32,
+}
+
+impl HotGraphicsPipeline {
+	/// `desc.shader_stages` is replaced by the compiled `shaders`.
+	pub fn new(
+		program_data: &ProgramData,
+		mut desc: GraphicsPipelineDesc,
+		shaders: &[(ShaderKind, &str, &[(&str, Option<&str>)])],
+		render_pass: &RenderPass,
+		spawners: &[&dyn BlockSpawnerGen],
+	) -> Result<Self> {
+		let samples = render_pass.subpass_sample_count(desc.subpass)?;
+		let color_attachment_count = render_pass.subpass_color_attachment_count(desc.subpass)?;
+		let shaders: Vec<HotShader> = shaders.iter().map(
+			|(shader_kind, name, defines)|
+			hot_shader(*shader_kind, name, defines)
+		).collect();
+		desc.shader_stages = load_stages(program_data, &shaders)?;
+		let pipeline = desc.build(
+			&program_data.device,
+			render_pass,
+			spawners,
+		);
+		destroy_stages(program_data, &desc.shader_stages);
+		desc.shader_stages.clear();
+		Ok(Self {
+			pipeline: pipeline?,
+			desc,
+			shaders,
+			render_pass: render_pass.render_pass,
+			samples,
+			color_attachment_count,
+		})
+	}
+
+	/// The pipeline must no longer be in use by the device.
+	pub fn destroy(
+		&self,
+		program_data: &ProgramData,
+	) {
+		self.pipeline.destroy(&program_data.device);
+	}
+}
+
+impl ShaderReload for HotGraphicsPipeline {
+	fn shaders(
+		&self,
+	) -> &[HotShader] {
+		&self.shaders
+	}
+
+	fn reload(
+		&mut self,
+		program_data: &ProgramData,
+	) -> Result<()> {
+		self.desc.shader_stages = load_stages(program_data, &self.shaders)?;
+		let pipeline = self.desc.create_pipeline(
+			&program_data.device,
+			self.render_pass,
+			self.samples,
+			self.color_attachment_count,
+			self.pipeline.layout,
+		);
+		destroy_stages(program_data, &self.desc.shader_stages);
+		self.desc.shader_stages.clear();
+		let pipeline = pipeline?;
+		program_data.deletion_queue.push(Deletion::Pipeline(
+			std::mem::replace(&mut self.pipeline.pipeline, pipeline),
+		));
+		Ok(())
+	}
+}
+
+/// [`ComputePipeline`] that can be rebuilt from its shader source; descriptor sets are kept.
+pub struct HotComputePipeline {
+	pub pipeline: ComputePipeline,
+	shaders: Vec<HotShader>,
+}
+
+impl HotComputePipeline {
+	/// `desc.shader_module` is replaced by the compiled `name`.
+	pub fn new(
+		program_data: &ProgramData,
+		mut desc: ComputePipelineDesc,
+		name: &str,
+		defines: &[(&str, Option<&str>)],
+	) -> Result<Self> {
+		let shaders = vec![hot_shader(ShaderKind::Compute, name, defines)];
+		let stages = load_stages(program_data, &shaders)?;
+		desc.shader_module = stages[0].0;
+		let pipeline = ComputePipeline::new(
+			program_data,
+			&desc,
+		);
+		destroy_stages(program_data, &stages);
+		Ok(Self {
+			pipeline: pipeline?,
+			shaders,
+		})
+	}
+
+	/// The pipeline must no longer be in use by the device.
+	pub fn destroy(
+		&self,
+		program_data: &ProgramData,
+	) -> Result<()> {
+		self.pipeline.destroy(program_data)
+	}
+}
+
+impl ShaderReload for HotComputePipeline {
+	fn shaders(
+		&self,
+	) -> &[HotShader] {
+		&self.shaders
+	}
+
+	fn reload(
+		&mut self,
+		program_data: &ProgramData,
+	) -> Result<()> {
+		let stages = load_stages(program_data, &self.shaders)?;
+		let pipeline = ComputePipeline::create_pipeline(
+			&program_data.device,
+			stages[0].0,
+			self.pipeline.layout,
+		);
+		destroy_stages(program_data, &stages);
+		let pipeline = pipeline?;
+		program_data.deletion_queue.push(Deletion::Pipeline(
+			std::mem::replace(&mut self.pipeline.pipeline, pipeline),
+		));
+		Ok(())
+	}
+}
+
+/// Dev mode shader hot reload; polls modification times under [`SHADER_ROOT`].
+/// Cached `.spv` files written next to the sources are ignored.
+pub struct ShaderWatcher {
+	root: PathBuf,
+	modified: HashMap<PathBuf, SystemTime>,
+}
+
+impl ShaderWatcher {
+	pub fn new(
+	) -> Self {
+		let root = PathBuf::from(SHADER_ROOT);
+		let mut modified = HashMap::new();
+		ShaderWatcher::scan(&root, &mut modified);
+		Self {
+			root,
+			modified,
+		}
+	}
+
+	fn scan(
+		directory: &Path,
+		modified: &mut HashMap<PathBuf, SystemTime>,
+	) {
+		let Ok(entries) = fs::read_dir(directory) else {
+			return;
+		};
+		for entry in entries.flatten() {
+			let path = entry.path();
+			let Ok(metadata) = entry.metadata() else {
+				continue;
+			};
+			if metadata.is_dir() {
+				ShaderWatcher::scan(&path, modified);
+			} else if path.extension().is_some_and(|extension| extension == "spv") {
+				continue;
+			} else if let Ok(time) = metadata.modified() {
+				modified.insert(path, time);
+			}
+		}
+	}
+
+	/// Files added or modified since the last poll.
+	pub fn poll(
+		&mut self,
+	) -> Vec<PathBuf> {
+		let mut modified = HashMap::with_capacity(self.modified.len());
+		ShaderWatcher::scan(&self.root, &mut modified);
+		let changed = modified.iter().filter(
+			|(path, time)|
+			self.modified.get(*path) != Some(*time)
+		).map(
+			|(path, _)|
+			path.clone()
+		).collect();
+		self.modified = modified;
+		changed
+	}
+
+	/// Rebuilds every pipeline with a changed stage or include and prints compile errors.
+	/// Includes found through search paths outside [`SHADER_ROOT`] are not watched.
+	/// Call between `FrameLoop::end_frame` and the next `begin_frame`; returns how many were rebuilt.
+	pub fn update(
+		&mut self,
+		program_data: &ProgramData,
+		pipelines: &mut [&mut dyn ShaderReload],
+	) -> usize {
+		let changed = self.poll();
+		if changed.is_empty() {
+			return 0;
+		}
+		let mut reloaded = 0;
+		for pipeline in pipelines.iter_mut() {
+			let uses_changed = pipeline.shaders().iter().filter_map(
+				|(shader_kind, name, _)|
+				shader_source_path(*shader_kind, name).ok()
+			).any(
+				|path|
+				std::iter::once(path.clone())
+					.chain(program_data.shader_loader.dependencies(&path))
+					.any(|path| changed.contains(&PathBuf::from(path)))
+			);
+			if !uses_changed {
+				continue;
+			}
+			match pipeline.reload(program_data) {
+				Ok(()) => reloaded += 1,
+				Err(error) => println!("shader reload failed, keeping the previous pipeline: {}", error),
+			}
+		}
+		reloaded
+	}
+}
\ No newline at end of file